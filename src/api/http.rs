@@ -0,0 +1,120 @@
+use std::io::{self, ErrorKind};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// Bodies are cut off here, health endpoints don't need more
+const MAX_RESPONSE_LEN: u64 = 64 * 1024;
+
+#[derive(Debug)]
+pub struct HttpUrl {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl HttpUrl {
+    /// Parses `http://host[:port][/path]`
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or(format!("unsupported url '{url}', expected http://"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            // ipv6 addresses are bracketed, so their colons aren't a port
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| format!("invalid port in url '{url}'"))?,
+            ),
+            _ => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in url '{url}'"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// A plain HTTP/1.1 GET, just enough to probe health endpoints
+pub async fn get(url: &HttpUrl) -> io::Result<HttpResponse> {
+    let mut stream = TcpStream::connect((url.host.trim_matches(['[', ']']), url.port)).await?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: server-tui\r\nConnection: close\r\n\r\n",
+        url.path, url.host
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = vec![];
+    stream
+        .take(MAX_RESPONSE_LEN)
+        .read_to_end(&mut response)
+        .await?;
+    parse_response(&String::from_utf8_lossy(&response))
+}
+
+fn parse_response(response: &str) -> io::Result<HttpResponse> {
+    let invalid = || io::Error::new(ErrorKind::InvalidData, "invalid http response");
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(invalid)?;
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(invalid)?;
+
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+
+    Ok(HttpResponse {
+        status,
+        body: if chunked {
+            dechunk(body)
+        } else {
+            body.to_string()
+        },
+    })
+}
+
+/// Joins the chunks of a chunked body, a truncated body keeps what arrived
+fn dechunk(mut body: &str) -> String {
+    let mut joined = String::new();
+    while let Some((size, rest)) = body.split_once("\r\n") {
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let Ok(size) = usize::from_str_radix(size, 16) else {
+            break;
+        };
+        if size == 0 {
+            break;
+        }
+
+        let chunk = rest.get(..size).unwrap_or(rest);
+        joined.push_str(chunk);
+        body = rest
+            .get(size..)
+            .unwrap_or_default()
+            .trim_start_matches("\r\n");
+    }
+    joined
+}
@@ -1 +1,5 @@
+pub mod http;
+pub mod socket;
 pub mod systemctl;
+pub mod tls;
+pub mod websocket;
@@ -0,0 +1,10 @@
+use std::{io::Write, os::unix::net::UnixStream, path::Path};
+
+use serde::Serialize;
+
+/// Sends a single request to a running instance
+pub fn send<T: Serialize>(socket_path: &Path, request: &T) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(socket_path)?;
+    stream.write_all(&serde_json::to_vec(request)?)?;
+    Ok(())
+}
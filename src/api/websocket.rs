@@ -0,0 +1,88 @@
+use std::io::{self, ErrorKind};
+
+use data_encoding::BASE64;
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Appended to the client's key for the handshake, from RFC 6455
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Clients only send control frames and the odd text message, nothing close to this
+const MAX_FRAME_LEN: u64 = 64 * 1024;
+
+pub const OPCODE_TEXT: u8 = 0x1;
+pub const OPCODE_CLOSE: u8 = 0x8;
+pub const OPCODE_PING: u8 = 0x9;
+pub const OPCODE_PONG: u8 = 0xA;
+
+pub struct Frame {
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+/// `Sec-WebSocket-Accept` for the client's `Sec-WebSocket-Key`
+pub fn accept_key(key: &str) -> String {
+    let hash = digest(
+        &SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{}{ACCEPT_GUID}", key.trim()).as_bytes(),
+    );
+    BASE64.encode(hash.as_ref())
+}
+
+/// A single unmasked frame, servers never mask
+pub fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    frame
+}
+
+/// Reads one frame from a client and unmasks it. Fragmented messages come back
+/// frame by frame, which is fine as long as we only look at control frames
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Frame> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header).await?;
+    let opcode = header[0] & 0x0F;
+    let masked = header[1] & 0x80 != 0;
+    let len = match header[1] & 0x7F {
+        126 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len).await?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len).await?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("websocket frame of {len} bytes is too long"),
+        ));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok(Frame { opcode, payload })
+}
@@ -1,77 +1,467 @@
 use std::{
-    io,
+    future, io,
+    path::PathBuf,
     sync::{Arc, Mutex},
     time::Duration,
 };
 
-use crossterm::event::{self, poll, KeyCode, KeyEventKind};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind};
+use futures_core::Stream;
+use log::{info, warn};
 use ratatui::{
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
     widgets::WidgetRef,
     DefaultTerminal,
 };
+use tokio::time::Instant;
 
 use crate::{
-    services::event_bus::EventBus,
-    utils,
+    models::config::Config,
+    models::{
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        event_type::EventType,
+        snapshot::{MetricSnapshot, Snapshot, SnapshotFormat},
+        socket_message::SocketQuery,
+        time_series::TimeSeries,
+    },
+    services::{
+        event_bus::EventBus,
+        history::StatusHistory,
+        plugins,
+        query::{QUERY_REPLY_TOPIC, QUERY_TOPIC},
+        render::RenderScheduler,
+        sampling::{DISPLAY_ACTIVE, DISPLAY_IDLE, DISPLAY_TOPIC},
+        scripts,
+        socket::SNAPSHOT_REQUEST_TOPIC,
+    },
+    utils::{self, duration::parse_duration, snapshot},
     widgets::{
-        current_status::CurrentStatusWidget, datetime::DateTimeWidget,
-        hardware::HardwareUsageWidget, podman::PodmanWidget,
+        certs::CertsWidget, checks::ChecksWidget, current_status::CurrentStatusWidget,
+        datetime::DateTimeWidget, hardware::HardwareUsageWidget, logs::LogsWidget,
+        plugins::PluginsWidget, podman::PodmanWidget, scripts::ScriptsWidget,
+        services::ServicesWidget, theme::Theme, timeline::TimelineWidget,
     },
 };
 
 pub struct App {
     terminal: DefaultTerminal,
+    scheduler: RenderScheduler,
     hw_usage: HardwareUsageWidget,
     status: CurrentStatusWidget,
     datetime: DateTimeWidget,
     podman: PodmanWidget,
+    services: ServicesWidget,
+    logs: LogsWidget,
+    checks: ChecksWidget,
+    certs: CertsWidget,
+    plugins: PluginsWidget,
+    scripts: ScriptsWidget,
+    /// The logs, checks and certificate panels only take up space when configured,
+    /// the plugins and scripts panels once there's something to show
+    log_count: usize,
+    check_count: usize,
+    cert_count: usize,
+    timeline: TimelineWidget,
+    show_timeline: bool,
     // logs: LogWidget,
+    event_bus: Arc<Mutex<EventBus>>,
+    display: Display,
+    /// Requested over the socket or with `x`, written after the next frame
+    snapshots: Arc<Mutex<Vec<(SnapshotFormat, PathBuf)>>>,
+    /// Socket queries by request ID, answered as soon as the loop gets to them
+    queries: Arc<Mutex<Vec<(String, SocketQuery)>>>,
+    /// Process names from the command line
+    watched: Vec<String>,
+}
+
+/// Whether anyone is looking, services sample less often while nobody is
+struct Display {
+    idle_after: Option<Duration>,
+    last_input: Instant,
+    no_input: bool,
+    focused: bool,
+    screens_off: bool,
+    idle: bool,
 }
 
+/// How often the screens' power state is checked
+const SCREEN_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 impl App {
     pub async fn new(
         mut terminal: DefaultTerminal,
         event_bus: Arc<Mutex<EventBus>>,
+        theme: Arc<Theme>,
+        config: &Config,
+        history: Arc<Mutex<StatusHistory>>,
+        idle_after: Option<Duration>,
+        watched: Vec<String>,
     ) -> io::Result<Self> {
         terminal.clear()?;
+        let snapshots = Arc::new(Mutex::new(vec![]));
+        App::subscribe_snapshots(&event_bus, Arc::clone(&snapshots));
+        let queries = Arc::new(Mutex::new(vec![]));
+        App::subscribe_queries(&event_bus, Arc::clone(&queries));
+        // plugins and scripts report metrics and statuses the same way
+        let reporters = config
+            .plugin
+            .iter()
+            .map(|plugin| plugins::topic(&plugin.name))
+            .chain(
+                config
+                    .script
+                    .iter()
+                    .map(|script| scripts::topic(&script.name)),
+            )
+            .collect::<Vec<String>>();
         Ok(Self {
             terminal,
-            hw_usage: HardwareUsageWidget::new(Arc::clone(&event_bus)),
-            status: CurrentStatusWidget::new(Arc::clone(&event_bus)).await,
-            datetime: DateTimeWidget::new(Arc::clone(&event_bus)),
-            podman: PodmanWidget::new(),
+            scheduler: RenderScheduler::new(Arc::clone(&event_bus)),
+            hw_usage: HardwareUsageWidget::new(
+                Arc::clone(&event_bus),
+                Arc::clone(&theme),
+                &config.charts,
+            ),
+            status: CurrentStatusWidget::new(
+                Arc::clone(&event_bus),
+                Arc::clone(&theme),
+                Arc::clone(&history),
+                &config.status,
+                &reporters,
+            )
+            .await,
+            datetime: DateTimeWidget::new(
+                Arc::clone(&event_bus),
+                Arc::clone(&theme),
+                &config.clock,
+            )?,
+            podman: PodmanWidget::new(Arc::clone(&theme)),
+            services: ServicesWidget::new(Arc::clone(&event_bus), Arc::clone(&theme)),
+            logs: LogsWidget::new(Arc::clone(&event_bus), Arc::clone(&theme), &config.logs)?,
+            log_count: config.logs.files.len(),
+            checks: ChecksWidget::new(Arc::clone(&event_bus), Arc::clone(&theme), &config.charts),
+            certs: CertsWidget::new(Arc::clone(&event_bus), Arc::clone(&theme)),
+            plugins: PluginsWidget::new(
+                Arc::clone(&event_bus),
+                Arc::clone(&theme),
+                &reporters,
+                &config.charts,
+            ),
+            scripts: ScriptsWidget::new(Arc::clone(&event_bus), Arc::clone(&theme)),
+            check_count: config.check.len(),
+            cert_count: config.certs.paths.len() + config.certs.endpoints.len(),
+            timeline: TimelineWidget::new(history, Arc::clone(&theme)),
+            show_timeline: false,
             // logs: LogWidget::new(),
+            event_bus,
+            display: Display {
+                idle_after,
+                last_input: Instant::now(),
+                no_input: false,
+                focused: true,
+                screens_off: false,
+                idle: false,
+            },
+            snapshots,
+            queries,
+            watched,
         })
     }
 
+    fn subscribe_queries(
+        event_bus: &Arc<Mutex<EventBus>>,
+        queries: Arc<Mutex<Vec<(String, SocketQuery)>>>,
+    ) {
+        event_bus
+            .lock()
+            .unwrap()
+            .subscribe(QUERY_TOPIC, move |data| {
+                let msg = EventBusMessage::from_bytes(data);
+                match serde_json::from_str(&msg.get_field_string(EventFieldType::Description)) {
+                    Ok(query) => queries
+                        .lock()
+                        .unwrap()
+                        .push((msg.title().to_string(), query)),
+                    Err(err) => warn!("invalid query {}: {err}", msg.title()),
+                }
+            });
+    }
+
+    /// Replies can't be published from the subscriber, the bus is locked there
+    fn answer_queries(&self) {
+        let queries = std::mem::take(&mut *self.queries.lock().unwrap());
+        for (id, query) in queries {
+            let reply = self.answer(&query).to_string();
+            self.event_bus.lock().unwrap().publish(
+                QUERY_REPLY_TOPIC,
+                EventBusMessage::new(
+                    &id,
+                    EventType::Socket,
+                    Some(vec![(EventFieldType::Description, reply.into_bytes())]),
+                )
+                .format_bytes(),
+            );
+        }
+    }
+
+    fn answer(&self, query: &SocketQuery) -> serde_json::Value {
+        match query {
+            SocketQuery::StatusList => {
+                let (statuses, snoozed) = self.status.snapshot();
+                serde_json::json!({ "statuses": statuses, "snoozed": snoozed })
+            }
+            SocketQuery::MetricsCurrent => serde_json::json!({ "metrics": self.metrics() }),
+            SocketQuery::MetricsRange { metric, since } => {
+                let from = match since.as_deref().map(parse_duration) {
+                    None => f64::MIN,
+                    Some(Some(secs)) => {
+                        chrono::Utc::now().timestamp_millis() as f64 / 1000.0 - secs as f64
+                    }
+                    Some(None) => {
+                        return serde_json::json!({ "error": format!("invalid duration '{}'", since.as_deref().unwrap_or_default()) })
+                    }
+                };
+                match self
+                    .series()
+                    .into_iter()
+                    .find(|series| series.name.eq_ignore_ascii_case(metric))
+                {
+                    Some(series) => serde_json::json!({
+                        "name": series.name,
+                        "unit": series.unit.to_string(),
+                        "samples": series.since(from),
+                    }),
+                    None => serde_json::json!({ "error": format!("unknown metric '{metric}'") }),
+                }
+            }
+            SocketQuery::ContainersList => {
+                serde_json::json!({ "containers": self.podman.snapshot() })
+            }
+            SocketQuery::ProcessesWatched => {
+                let processes = self
+                    .watched
+                    .iter()
+                    .map(|name| {
+                        let last_seen = self.status.last_seen(name);
+                        serde_json::json!({
+                            "name": name,
+                            "running": last_seen.is_some(),
+                            "last_seen": last_seen,
+                        })
+                    })
+                    .collect::<Vec<serde_json::Value>>();
+                serde_json::json!({ "processes": processes })
+            }
+            // streamed by the socket service itself
+            SocketQuery::Subscribe { .. } => {
+                serde_json::json!({ "error": "subscriptions aren't answered by the app" })
+            }
+        }
+    }
+
+    /// Every series the panels keep, hardware first
+    fn series(&self) -> Vec<TimeSeries> {
+        let mut series = self.hw_usage.series();
+        series.extend(self.checks.series());
+        series.extend(self.plugins.series());
+        series
+    }
+
+    fn metrics(&self) -> Vec<MetricSnapshot> {
+        let mut metrics = self
+            .series()
+            .iter()
+            .filter_map(MetricSnapshot::from_series)
+            .collect::<Vec<MetricSnapshot>>();
+        metrics.extend(self.services.self_usage_snapshot());
+        metrics
+    }
+
+    fn subscribe_snapshots(
+        event_bus: &Arc<Mutex<EventBus>>,
+        snapshots: Arc<Mutex<Vec<(SnapshotFormat, PathBuf)>>>,
+    ) {
+        event_bus
+            .lock()
+            .unwrap()
+            .subscribe(SNAPSHOT_REQUEST_TOPIC, move |data| {
+                let msg = EventBusMessage::from_bytes(data);
+                let format =
+                    SnapshotFormat::from_string(&msg.get_field_string(EventFieldType::State));
+                let path = PathBuf::from(msg.get_field_string(EventFieldType::Description));
+                snapshots.lock().unwrap().push((format, path));
+            });
+    }
+
+    /// Every format at once, next to the history in the state directory
+    fn request_snapshot(&self) {
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let mut snapshots = self.snapshots.lock().unwrap();
+        for format in [
+            SnapshotFormat::Text,
+            SnapshotFormat::Json,
+            SnapshotFormat::Html,
+        ] {
+            let name = format!("snapshot-{stamp}.{}", format.extension());
+            match utils::config::state_path(&name) {
+                Some(path) => snapshots.push((format, path)),
+                None => warn!("no state directory to write {name} to"),
+            }
+        }
+    }
+
+    /// The state behind the panels, as far as they keep any
+    fn snapshot(&self) -> Snapshot {
+        let (statuses, snoozed) = self.status.snapshot();
+        Snapshot {
+            taken: chrono::Local::now().to_rfc3339(),
+            statuses,
+            snoozed,
+            metrics: self.metrics(),
+            containers: self.podman.snapshot(),
+            services: self.services.snapshot(),
+        }
+    }
+
+    fn write_snapshots(&self, buf: &Buffer, snapshots: Vec<(SnapshotFormat, PathBuf)>) {
+        for (format, path) in snapshots {
+            let contents = match format {
+                SnapshotFormat::Text => Ok(snapshot::to_text(buf)),
+                SnapshotFormat::Json => serde_json::to_string_pretty(&self.snapshot()),
+                SnapshotFormat::Html => Ok(snapshot::to_html(
+                    buf,
+                    &format!("server-tui {}", chrono::Local::now().format("%F %T")),
+                )),
+            };
+            match contents
+                .map_err(io::Error::from)
+                .and_then(|contents| snapshot::write_new(&path, &contents))
+            {
+                Ok(()) => info!(
+                    "{} snapshot written to {}",
+                    format.to_string(),
+                    path.display()
+                ),
+                Err(err) => warn!("unable to write snapshot to {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// Only redraws when something was published, a key was pressed or the
+    /// terminal was resized, at most once per frame interval
     pub async fn run(&mut self) -> io::Result<()> {
+        let mut events = EventStream::new();
+        let mut screen_check = tokio::time::interval(SCREEN_CHECK_INTERVAL);
+        let mut dirty = true;
         loop {
-            // self.logs.poll();
-
-            self.draw()?;
+            if dirty {
+                self.scheduler.next_frame().await;
+                self.draw()?;
+                dirty = false;
+            }
 
-            if poll(Duration::from_millis(100))? {
-                if let event::Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
-                        return Ok(());
+            let idle_at = self
+                .display
+                .idle_after
+                .map(|idle_after| self.display.last_input + idle_after);
+            tokio::select! {
+                _ = self.scheduler.dirty() => {
+                    self.answer_queries();
+                    dirty = true;
+                }
+                _ = tokio::time::sleep_until(idle_at.unwrap_or_else(Instant::now)),
+                    if idle_at.is_some() && !self.display.no_input =>
+                {
+                    self.display.no_input = true;
+                    self.update_idle();
+                }
+                _ = screen_check.tick() => {
+                    self.display.screens_off = utils::display::screens_off();
+                    self.update_idle();
+                }
+                event = future::poll_fn(|cx| std::pin::Pin::new(&mut events).poll_next(cx)) => {
+                    match event {
+                        Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                            if !self.handle_key(key) {
+                                return Ok(());
+                            }
+                            self.display.last_input = Instant::now();
+                            self.display.no_input = false;
+                            self.update_idle();
+                            dirty = true;
+                        }
+                        Some(Ok(Event::FocusGained)) => {
+                            self.display.focused = true;
+                            self.display.last_input = Instant::now();
+                            self.display.no_input = false;
+                            self.update_idle();
+                        }
+                        Some(Ok(Event::FocusLost)) => {
+                            self.display.focused = false;
+                            self.update_idle();
+                        }
+                        Some(Ok(Event::Resize(_, _))) => dirty = true,
+                        Some(Ok(_)) => (),
+                        Some(Err(err)) => return Err(err),
+                        // stdin is gone, nobody is watching anymore
+                        None => return Ok(()),
                     }
                 }
             }
         }
     }
 
+    /// Tells the services when nobody is looking anymore, or someone is again
+    fn update_idle(&mut self) {
+        let display = &mut self.display;
+        let idle = display.no_input || !display.focused || display.screens_off;
+        if idle == display.idle {
+            return;
+        }
+        display.idle = idle;
+        self.scheduler.set_idle(idle);
+
+        let state = if idle { DISPLAY_IDLE } else { DISPLAY_ACTIVE };
+        info!("display is {state}");
+        self.event_bus.lock().unwrap().publish(
+            DISPLAY_TOPIC,
+            EventBusMessage::new(
+                DISPLAY_TOPIC,
+                EventType::Service,
+                Some(vec![(EventFieldType::State, state.as_bytes().to_vec())]),
+            )
+            .format_bytes(),
+        );
+    }
+
+    /// Returns false when the app should quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        // popups get every key, so typing a filter doesn't quit the app
+        if self.show_timeline {
+            self.show_timeline = self.timeline.handle_key(key);
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('h') => self.show_timeline = true,
+            KeyCode::Char('x') => self.request_snapshot(),
+            _ => self.status.handle_key(key),
+        }
+        true
+    }
+
     fn draw(&mut self) -> io::Result<()> {
-        self.terminal.draw(|frame| {
+        let frame = self.terminal.draw(|frame| {
             let master_layout = utils::layout::make_layout(Direction::Vertical, 2);
 
             let layout = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints(vec![Constraint::Percentage(25), Constraint::Percentage(75)]);
 
-            let layout_ver = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)]);
+            let layout_ver = utils::layout::make_layout(Direction::Vertical, 3);
 
             // let blocks: Vec<Box<dyn WidgetRef>> = vec![
             //     Box::new(SystemctlWidget::new()),
@@ -80,7 +470,7 @@ impl App {
 
             let [upper_area, hardware_area] = master_layout.areas(frame.area());
             let [stat_areas, log_area]: [Rect; 2] = layout.areas(upper_area);
-            let status_areas: [Rect; 2] = layout_ver.areas(stat_areas);
+            let status_areas: [Rect; 3] = layout_ver.areas(stat_areas);
 
             // assert!(blocks.len() == status_areas.len());
 
@@ -92,12 +482,57 @@ impl App {
                 .render_ref(status_areas[0], frame.buffer_mut());
 
             self.podman.render_ref(status_areas[1], frame.buffer_mut());
+            self.services
+                .render_ref(status_areas[2], frame.buffer_mut());
 
             // self.logs.render(log_area, frame.buffer_mut());
-            self.status.render_ref(log_area, frame.buffer_mut());
+            // optional panels get a line per entry, but never more than a third each
+            let panel = |count: usize| match count {
+                0 => Constraint::Length(0),
+                count => Constraint::Length((count as u16 + 2).min(log_area.height / 3)),
+            };
+            let plugin_count = self.plugins.count();
+            let script_count = self.scripts.count();
+            let [status_area, logs_area, checks_area, certs_area, plugins_area, scripts_area] =
+                Layout::vertical([
+                    Constraint::Fill(1),
+                    // logs share the space left over with the status panel
+                    Constraint::Fill(self.log_count.min(1) as u16),
+                    panel(self.check_count),
+                    panel(self.cert_count),
+                    panel(plugin_count),
+                    panel(script_count),
+                ])
+                .areas(log_area);
+
+            self.status.render_ref(status_area, frame.buffer_mut());
+            if self.log_count > 0 {
+                self.logs.render_ref(logs_area, frame.buffer_mut());
+            }
+            if self.check_count > 0 {
+                self.checks.render_ref(checks_area, frame.buffer_mut());
+            }
+            if self.cert_count > 0 {
+                self.certs.render_ref(certs_area, frame.buffer_mut());
+            }
+            if plugin_count > 0 {
+                self.plugins.render_ref(plugins_area, frame.buffer_mut());
+            }
+            if script_count > 0 {
+                self.scripts.render_ref(scripts_area, frame.buffer_mut());
+            }
             self.hw_usage.render_ref(hardware_area, frame.buffer_mut());
+
+            if self.show_timeline {
+                self.timeline.render_ref(frame.area(), frame.buffer_mut());
+            }
         })?;
 
+        let snapshots = std::mem::take(&mut *self.snapshots.lock().unwrap());
+        if !snapshots.is_empty() {
+            let buf = frame.buffer.clone();
+            self.write_snapshots(&buf, snapshots);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,38 @@
+use std::io;
+
+use crate::{
+    api,
+    models::socket_message::HeartbeatMessage,
+    services::socket::{SocketService, SOCKET_NAME},
+    utils::duration::parse_duration,
+};
+
+use super::usage_error;
+
+const USAGE: &str = "server-tui heartbeat <name> [--every <duration>] [--grace <duration>]";
+
+/// `server-tui heartbeat db-dump --every 24h --grace 1h`, pings a heartbeat and
+/// registers it if it isn't in the config
+pub fn run(args: &[String]) -> io::Result<()> {
+    let (name, options) = args.split_first().ok_or(usage_error(USAGE))?;
+    let mut msg = HeartbeatMessage {
+        heartbeat: name.clone(),
+        every: None,
+        grace: None,
+    };
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .filter(|value| parse_duration(value).is_some())
+            .ok_or(usage_error(USAGE))?;
+        match option.as_str() {
+            "--every" => msg.every = Some(value.clone()),
+            "--grace" => msg.grace = Some(value.clone()),
+            _ => return Err(usage_error(USAGE)),
+        }
+    }
+
+    api::socket::send(&SocketService::socket_path(SOCKET_NAME), &msg)
+}
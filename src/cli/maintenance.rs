@@ -0,0 +1,47 @@
+use std::io;
+
+use crate::{
+    api,
+    models::{maintenance::MaintenanceMode, socket_message::SocketCommand},
+    services::socket::{SocketService, SOCKET_NAME},
+    utils::duration::parse_duration,
+};
+
+use super::usage_error;
+
+const USAGE: &str = "server-tui maintenance start <duration> [reason] [--downgrade] | end";
+
+/// `server-tui maintenance start 2h "kernel upgrade"` or `server-tui maintenance end`
+pub fn run(args: &[String]) -> io::Result<()> {
+    let command = match args.first().map(|arg| arg.as_str()) {
+        Some("start") => {
+            let duration = args.get(1).ok_or(usage_error(USAGE))?;
+            if parse_duration(duration).is_none() {
+                return Err(usage_error(USAGE));
+            }
+
+            let mode = if args.iter().any(|arg| arg == "--downgrade") {
+                MaintenanceMode::Downgrade
+            } else {
+                MaintenanceMode::Suppress
+            };
+            let reason = args
+                .iter()
+                .skip(2)
+                .filter(|arg| *arg != "--downgrade")
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            SocketCommand::MaintenanceStart {
+                duration: duration.clone(),
+                reason,
+                mode,
+            }
+        }
+        Some("end") => SocketCommand::MaintenanceEnd,
+        _ => return Err(usage_error(USAGE)),
+    };
+
+    api::socket::send(&SocketService::socket_path(SOCKET_NAME), &command)
+}
@@ -0,0 +1,39 @@
+use std::io::{self, ErrorKind};
+
+pub mod heartbeat;
+pub mod maintenance;
+pub mod run;
+pub mod snapshot;
+pub mod status;
+
+/// Client side subcommands, talking to a running instance over the socket.
+/// Returns None if the arguments don't start with a subcommand
+pub async fn dispatch(args: &[String]) -> Option<io::Result<()>> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "heartbeat" => Some(heartbeat::run(rest)),
+        "maintenance" => Some(maintenance::run(rest)),
+        "run" => Some(run::run(rest).await),
+        "snapshot" => Some(snapshot::run(rest).await),
+        "status" => Some(status::run(rest)),
+        _ => None,
+    }
+}
+
+pub fn usage_error(usage: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, format!("usage: {usage}"))
+}
+
+/// Removes `flag` and the value after it from `args`, for options of the dashboard
+/// itself. Everything left is a process to watch
+pub fn take_flag(args: &mut Vec<String>, flag: &str) -> io::Result<Option<String>> {
+    let Some(position) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    if position + 1 >= args.len() {
+        return Err(usage_error(&format!("{flag} <value>")));
+    }
+    let value = args.remove(position + 1);
+    args.remove(position);
+    Ok(Some(value))
+}
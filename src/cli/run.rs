@@ -0,0 +1,208 @@
+use std::{
+    io,
+    os::unix::process::ExitStatusExt,
+    path::PathBuf,
+    process::{ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    process::Command,
+    signal::unix::{signal, SignalKind},
+};
+
+use crate::{
+    api,
+    models::socket_message::SocketMessage,
+    services::socket::{SocketService, SOCKET_DONE_TEXT, SOCKET_FAILED_TEXT, SOCKET_NAME},
+    utils::duration::format_duration,
+};
+
+use super::usage_error;
+
+const USAGE: &str = "server-tui run [--title <title>] -- <command> [args...]";
+/// Output is forwarded as it comes, but the status only every so often
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_DESCRIPTION_LEN: usize = 120;
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(1);
+/// Marks output lines, so a command printing `done` or `failed ...` doesn't
+/// close its own job, only the exit status does that
+const OUTPUT_PREFIX: &str = "> ";
+
+/// Sends statuses for one job, gives up quietly after the first failure so the
+/// command still runs when no instance is listening
+struct Reporter {
+    socket_path: PathBuf,
+    title: String,
+    enabled: bool,
+}
+
+impl Reporter {
+    fn send(&mut self, status: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        let msg = SocketMessage {
+            title: self.title.clone(),
+            status: status.to_string(),
+        };
+        if let Err(err) = api::socket::send(&self.socket_path, &msg) {
+            eprintln!(
+                "server-tui: not reporting status of '{}': {err}",
+                self.title
+            );
+            self.enabled = false;
+        }
+    }
+}
+
+/// `server-tui run --title nightly-backup -- ./backup.sh`, runs the command and
+/// reports running, its last output line, and done or failed to the status panel.
+/// Exits with the exit code of the command
+pub async fn run(args: &[String]) -> io::Result<()> {
+    let (title, command) = parse_args(args)?;
+    let mut reporter = Reporter {
+        socket_path: SocketService::socket_path(SOCKET_NAME),
+        title,
+        enabled: true,
+    };
+
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id().map(|pid| pid as i32);
+    let started = Instant::now();
+    reporter.send("running");
+
+    let last_line = Arc::new(Mutex::new(None));
+    let mut forwarders = vec![];
+    if let Some(stdout) = child.stdout.take() {
+        forwarders.push(tokio::spawn(forward(
+            stdout,
+            tokio::io::stdout(),
+            Arc::clone(&last_line),
+        )));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forwarders.push(tokio::spawn(forward(
+            stderr,
+            tokio::io::stderr(),
+            Arc::clone(&last_line),
+        )));
+    }
+
+    // a ctrl-c in the terminal reaches the child too, so it may see SIGINT twice
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut quit = signal(SignalKind::quit())?;
+    let mut update = tokio::time::interval(UPDATE_INTERVAL);
+
+    let status = loop {
+        let forwarded = tokio::select! {
+            status = child.wait() => break status?,
+            _ = interrupt.recv() => libc::SIGINT,
+            _ = terminate.recv() => libc::SIGTERM,
+            _ = hangup.recv() => libc::SIGHUP,
+            _ = quit.recv() => libc::SIGQUIT,
+            _ = update.tick() => {
+                if let Some(line) = last_line.lock().unwrap().take() {
+                    reporter.send(&line);
+                }
+                continue;
+            }
+        };
+
+        if let Some(pid) = pid {
+            // SAFETY: kill has no memory safety requirements, the pid is our own child
+            unsafe { libc::kill(pid, forwarded) };
+        }
+    };
+
+    // background processes can keep the pipes open, don't wait on them forever
+    for forwarder in forwarders {
+        let _ = tokio::time::timeout(FORWARD_TIMEOUT, forwarder).await;
+    }
+
+    let took = format_duration(started.elapsed().as_secs());
+    if status.success() {
+        reporter.send(SOCKET_DONE_TEXT);
+    } else {
+        reporter.send(&format!(
+            "{SOCKET_FAILED_TEXT}: {} after {took}",
+            describe(status)
+        ));
+    }
+
+    std::process::exit(exit_code(status));
+}
+
+/// Returns the title and the command, the title defaults to the program name
+fn parse_args(args: &[String]) -> io::Result<(String, Vec<String>)> {
+    let separator = args
+        .iter()
+        .position(|arg| arg == "--")
+        .ok_or(usage_error(USAGE))?;
+    let (options, command) = (&args[..separator], args[separator + 1..].to_vec());
+    if command.is_empty() {
+        return Err(usage_error(USAGE));
+    }
+
+    let title = match options {
+        [] => PathBuf::from(&command[0])
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(command[0].clone()),
+        [flag, title] if flag == "--title" || flag == "-t" => title.clone(),
+        _ => return Err(usage_error(USAGE)),
+    };
+
+    Ok((title, command))
+}
+
+/// Copies `input` to `output` line by line, keeping the last non-empty line
+async fn forward(
+    input: impl AsyncRead + Unpin,
+    mut output: impl AsyncWrite + Unpin,
+    last_line: Arc<Mutex<Option<String>>>,
+) {
+    let mut lines = BufReader::new(input).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if output
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .and(output.flush().await)
+            .is_err()
+        {
+            break;
+        }
+
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            let line: String = trimmed.chars().take(MAX_DESCRIPTION_LEN).collect();
+            *last_line.lock().unwrap() = Some(format!("{OUTPUT_PREFIX}{line}"));
+        }
+    }
+}
+
+fn describe(status: ExitStatus) -> String {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => format!("exit {code}"),
+        (None, Some(signal)) => format!("killed by signal {signal}"),
+        (None, None) => "unknown exit status".to_string(),
+    }
+}
+
+/// Mirrors the shell, `128 + signal` when the command was killed
+fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or(status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
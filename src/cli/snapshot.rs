@@ -0,0 +1,70 @@
+use std::{
+    env,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{
+    api,
+    models::{snapshot::SnapshotFormat, socket_message::SocketCommand},
+    services::socket::{SocketService, SOCKET_NAME},
+};
+
+use super::usage_error;
+
+const USAGE: &str = "server-tui snapshot [text|json|html] [path]";
+/// How long the running instance gets to write the file
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// `server-tui snapshot html /tmp/incident.html`, has the running instance write
+/// what it shows and prints the path once it's there. Defaults to text in
+/// `./server-tui-<timestamp>.txt`
+pub async fn run(args: &[String]) -> io::Result<()> {
+    if args.len() > 2 {
+        return Err(usage_error(USAGE));
+    }
+    let format = match args.first().map(String::as_str) {
+        None => SnapshotFormat::Text,
+        Some(format @ ("text" | "json" | "html")) => SnapshotFormat::from_string(format),
+        Some(_) => return Err(usage_error(USAGE)),
+    };
+    let path = match args.get(1) {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(format!(
+            "server-tui-{}.{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            format.extension()
+        )),
+    };
+    // the instance has its own working directory
+    let path = env::current_dir()?.join(path);
+    if path.exists() {
+        return Err(io::Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} already exists", path.display()),
+        ));
+    }
+
+    api::socket::send(
+        &SocketService::socket_path(SOCKET_NAME),
+        &SocketCommand::Snapshot {
+            format,
+            path: path.to_string_lossy().to_string(),
+        },
+    )?;
+
+    let started = tokio::time::Instant::now();
+    while started.elapsed() < WRITE_TIMEOUT {
+        if path.exists() {
+            println!("{}", path.display());
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(io::Error::new(
+        ErrorKind::TimedOut,
+        format!("no snapshot written to {}", path.display()),
+    ))
+}
@@ -0,0 +1,33 @@
+use std::io;
+
+use crate::{
+    api,
+    models::socket_message::SocketCommand,
+    services::socket::{SocketService, SOCKET_NAME},
+    utils::duration::parse_duration,
+};
+
+use super::usage_error;
+
+const USAGE: &str = "server-tui status ack <title> | dismiss <title> | snooze <title> [duration]";
+
+/// `server-tui status snooze "db backup" 1h`, the same actions as on the status panel
+pub fn run(args: &[String]) -> io::Result<()> {
+    let title = args.get(1).ok_or(usage_error(USAGE))?.clone();
+    let command = match (args[0].as_str(), args.get(2)) {
+        ("ack", None) => SocketCommand::StatusAck { title },
+        ("dismiss", None) => SocketCommand::StatusDismiss { title },
+        ("snooze", duration) if args.len() <= 3 => {
+            if duration.is_some_and(|duration| parse_duration(duration).is_none()) {
+                return Err(usage_error(USAGE));
+            }
+            SocketCommand::StatusSnooze {
+                title,
+                duration: duration.cloned(),
+            }
+        }
+        _ => return Err(usage_error(USAGE)),
+    };
+
+    api::socket::send(&SocketService::socket_path(SOCKET_NAME), &command)
+}
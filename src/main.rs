@@ -1,21 +1,43 @@
 use std::{
     env,
     fs::File,
-    io,
+    io::{self, ErrorKind, IsTerminal, Write},
+    path::Path,
     sync::{Arc, Mutex},
 };
 
-use crate::traits::runnable::Runnable;
 use app::App;
+use crossterm::{
+    event::{DisableFocusChange, EnableFocusChange},
+    execute,
+};
 use log::LevelFilter;
 use services::{
-    datetime::DateTimeService, event_bus::EventBus, hw_usage::HwUsageService,
-    process_watcher::ProcessWatcher, socket::SocketService,
+    certs::CertService,
+    datetime::DateTimeService,
+    event_bus::EventBus,
+    file_tail::FileTailService,
+    health_check::HealthCheckService,
+    heartbeat::{HeartbeatService, HEARTBEAT_FILE},
+    history::{StatusHistory, HISTORY_FILE},
+    http_api::HttpApiService,
+    hw_usage::HwUsageService,
+    maintenance::MaintenanceService,
+    plugins::PluginService,
+    process_watcher::ProcessWatcher,
+    recording::{Recorder, Replayer},
+    sampling::{self, Sampling},
+    scripts::ScriptService,
+    socket::{SocketService, SOCKET_NAME},
+    supervisor::Supervisor,
 };
 use simplelog::{CombinedLogger, Config, WriteLogger};
+use utils::instance::InstanceLock;
+use widgets::theme::Theme;
 
 mod api;
 mod app;
+mod cli;
 mod models;
 mod services;
 mod traits;
@@ -24,6 +46,21 @@ mod widgets;
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    if let Some(result) = cli::dispatch(&args).await {
+        return result;
+    }
+    let record = cli::take_flag(&mut args, "--record")?;
+    let replay = cli::take_flag(&mut args, "--replay")?;
+    let speed = match cli::take_flag(&mut args, "--speed")? {
+        Some(speed) => speed
+            .parse::<f64>()
+            .ok()
+            .filter(|speed| *speed > 0.0)
+            .ok_or(cli::usage_error("--speed <factor above 0>"))?,
+        None => 1.0,
+    };
+
     CombinedLogger::init(vec![WriteLogger::new(
         LevelFilter::Info,
         Config::default(),
@@ -31,24 +68,160 @@ async fn main() -> io::Result<()> {
     )])
     .unwrap();
 
+    let config = utils::config::load()?;
+    let theme = Arc::new(Theme::from_config(&config.theme)?);
+
     let event_bus = Arc::new(Mutex::new(EventBus::new()));
+    let recorder = record
+        .map(|path| Recorder::new(&event_bus, Path::new(&path)))
+        .transpose()?;
+    // held until we return, dropping it unlinks the socket. Replays don't have
+    // a socket, they'd only take it from a live instance
+    let instance_lock = match replay {
+        Some(_) => None,
+        None => match InstanceLock::acquire(&SocketService::socket_path(SOCKET_NAME)) {
+            Ok(lock) => Some(lock),
+            Err(err) if err.kind() == ErrorKind::AddrInUse => {
+                if !ask_to_attach(&err)? {
+                    return Ok(());
+                }
+                None
+            }
+            Err(err) => return Err(err),
+        },
+    };
 
-    let to_watch = env::args().skip(1).collect::<Vec<String>>();
-    let services: Vec<Box<dyn Runnable>> = vec![
-        Box::new(SocketService::new(Arc::clone(&event_bus), "server-tui.sock").await),
-        Box::new(ProcessWatcher::new(Arc::clone(&event_bus), to_watch)),
-        Box::new(HwUsageService::new(Arc::clone(&event_bus))),
-        Box::new(DateTimeService::new(Arc::clone(&event_bus))),
-    ];
+    // state files belong to whoever holds the socket. An attached instance would
+    // rewrite them under the running one, and a replay didn't really happen
+    let state_path = |file_name| {
+        instance_lock
+            .as_ref()
+            .and_then(|_| utils::config::state_path(file_name))
+    };
+    let (history, history_writer) = StatusHistory::load(state_path(HISTORY_FILE));
+    let history = Arc::new(Mutex::new(history));
+
+    let to_watch = args.clone();
+    let mut supervisor = Supervisor::new(Arc::clone(&event_bus));
+    let sampling = &config.sampling;
+    let idle_after = sampling::idle_after(sampling)?;
+    if let Some(recorder) = recorder {
+        supervisor.add(Box::new(recorder));
+    }
+    if let Some(history_writer) = history_writer {
+        supervisor.add(Box::new(history_writer));
+    }
+    // a replay stands in for every real service
+    if let Some(path) = &replay {
+        supervisor.add(Box::new(Replayer::new(
+            Arc::clone(&event_bus),
+            Path::new(path),
+            speed,
+        )?));
+    } else {
+        supervisor.add(Box::new(ProcessWatcher::new(
+            Arc::clone(&event_bus),
+            to_watch,
+            Sampling::new(
+                &event_bus,
+                "processes",
+                &sampling.processes,
+                sampling.idle_factor,
+            )?,
+        )));
+        supervisor.add(Box::new(HwUsageService::new(
+            Arc::clone(&event_bus),
+            Sampling::new(
+                &event_bus,
+                "hardware",
+                &sampling.hardware,
+                sampling.idle_factor,
+            )?,
+        )));
+        supervisor.add(Box::new(DateTimeService::new(Arc::clone(&event_bus))));
+        supervisor.add(Box::new(MaintenanceService::new(
+            Arc::clone(&event_bus),
+            &config.maintenance,
+        )?));
+        supervisor.add(Box::new(HeartbeatService::new(
+            Arc::clone(&event_bus),
+            &config.heartbeat,
+            state_path(HEARTBEAT_FILE),
+        )?));
+        supervisor.add(Box::new(HealthCheckService::new(
+            Arc::clone(&event_bus),
+            &config.check,
+        )?));
+        supervisor.add(Box::new(PluginService::new(
+            Arc::clone(&event_bus),
+            &config.plugin,
+        )?));
+        supervisor.add(Box::new(ScriptService::new(
+            Arc::clone(&event_bus),
+            &config.script,
+        )?));
+        supervisor.add(Box::new(FileTailService::new(
+            Arc::clone(&event_bus),
+            &config.logs,
+        )?));
+        supervisor.add(Box::new(CertService::new(
+            Arc::clone(&event_bus),
+            &config.certs,
+        )?));
 
-    for s in services {
-        s.run();
+        if let Some(lock) = &instance_lock {
+            supervisor.add(Box::new(SocketService::new(
+                Arc::clone(&event_bus),
+                lock.socket_path(),
+            )?));
+            // an attached instance would only find the port taken
+            if config.http.enabled {
+                supervisor.add(Box::new(HttpApiService::new(
+                    Arc::clone(&event_bus),
+                    &config.http,
+                )?));
+            }
+        }
     }
 
     let terminal = ratatui::init();
-    let mut app = App::new(terminal, Arc::clone(&event_bus)).await?;
+    // losing focus counts as nobody looking, in terminals that report it
+    execute!(io::stdout(), EnableFocusChange)?;
+    // the app subscribes to the event bus, so it has to exist before the services
+    // publish their initial state
+    let mut app = App::new(
+        terminal,
+        Arc::clone(&event_bus),
+        theme,
+        &config,
+        history,
+        idle_after,
+        args,
+    )
+    .await?;
+    supervisor.start_all();
     let result = app.run().await;
 
+    supervisor.shutdown().await;
+
+    let _ = execute!(io::stdout(), DisableFocusChange);
     ratatui::restore();
+    drop(instance_lock);
     result
 }
+
+/// Another instance owns the socket. Attaching starts the dashboard without the
+/// socket service, so messages sent to the socket keep going to the other instance
+fn ask_to_attach(err: &io::Error) -> io::Result<bool> {
+    eprintln!("{err}");
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    eprint!("[a]ttach without the socket, or [e]xit? ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("a"))
+}
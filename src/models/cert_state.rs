@@ -0,0 +1,27 @@
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CertState {
+    Valid,
+    /// Expires within the warning threshold
+    Expiring,
+    /// Expires within the error threshold, already expired or couldn't be read
+    Critical,
+}
+
+impl CertState {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "valid" => CertState::Valid,
+            "expiring" => CertState::Expiring,
+            "critical" => CertState::Critical,
+            &_ => panic!("invalid certificate state, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            CertState::Valid => "valid",
+            CertState::Expiring => "expiring",
+            CertState::Critical => "critical",
+        }
+    }
+}
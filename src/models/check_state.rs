@@ -0,0 +1,22 @@
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CheckState {
+    Passing,
+    Failing,
+}
+
+impl CheckState {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "passing" => CheckState::Passing,
+            "failing" => CheckState::Failing,
+            &_ => panic!("invalid check state, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            CheckState::Passing => "passing",
+            CheckState::Failing => "failing",
+        }
+    }
+}
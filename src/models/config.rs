@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::{
+    maintenance::MaintenanceMode,
+    metric_style::MetricStyle,
+    status_order::{GroupBy, SortKey},
+};
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    pub clock: ClockConfig,
+    pub status: StatusConfig,
+    pub sampling: SamplingConfig,
+    pub charts: ChartsConfig,
+    pub maintenance: Vec<MaintenanceConfig>,
+    pub heartbeat: Vec<HeartbeatConfig>,
+    pub check: Vec<HealthCheckConfig>,
+    pub plugin: Vec<PluginConfig>,
+    pub script: Vec<ScriptConfig>,
+    pub certs: CertConfig,
+    pub logs: LogConfig,
+    pub http: HttpConfig,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// One of the built-in palettes, see `Theme::from_config`
+    pub name: Option<String>,
+    /// Style overrides per widget and slot, e.g. `status.title = "yellow bold"`
+    pub widgets: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// strftime format for the local time
+    pub format: String,
+    pub date_format: String,
+    /// Extra IANA timezones shown below the local time, e.g. `America/New_York`
+    pub timezones: Vec<String>,
+    pub calendar: bool,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            format: "%H:%M:%S".to_string(),
+            date_format: "%a %d %b %Y".to_string(),
+            timezones: vec![],
+            calendar: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// Sort keys by priority, the title breaks any remaining ties
+    pub order: Vec<SortKey>,
+    pub group_by: GroupBy,
+    /// Titles always shown first, in this order
+    pub pin: Vec<String>,
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            order: vec![SortKey::Severity, SortKey::Since, SortKey::Title],
+            group_by: GroupBy::Type,
+            pin: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct SamplingConfig {
+    /// CPU, memory and our own usage
+    pub hardware: String,
+    /// Watched processes
+    pub processes: String,
+    /// Without input for this long the display counts as idle, unset to only go
+    /// idle when the terminal loses focus or the screens are off
+    pub idle_after: Option<String>,
+    /// Intervals are multiplied by this while the display is idle
+    pub idle_factor: u32,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            hardware: "1s".to_string(),
+            processes: "2s".to_string(),
+            idle_after: None,
+            idle_factor: 5,
+        }
+    }
+}
+
+/// The visual used by each panel showing values over time
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ChartsConfig {
+    /// CPU and memory
+    pub usage: MetricStyle,
+    pub load: MetricStyle,
+    pub network: MetricStyle,
+    /// Health check latencies
+    pub checks: MetricStyle,
+    /// Metrics reported by plugins and scripts
+    pub plugins: MetricStyle,
+}
+
+impl Default for ChartsConfig {
+    fn default() -> Self {
+        Self {
+            usage: MetricStyle::Line,
+            load: MetricStyle::Line,
+            network: MetricStyle::Line,
+            checks: MetricStyle::Sparkline,
+            plugins: MetricStyle::Sparkline,
+        }
+    }
+}
+
+/// A recurring maintenance window
+#[derive(Deserialize, Debug)]
+pub struct MaintenanceConfig {
+    /// Five field cron expression for when the window opens, in local time
+    pub cron: String,
+    /// How long the window stays open, e.g. `2h`
+    pub duration: String,
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub mode: MaintenanceMode,
+}
+
+/// Expects a ping named `name` at least every `every`, plus `grace`
+#[derive(Deserialize, Debug)]
+pub struct HeartbeatConfig {
+    pub name: String,
+    /// e.g. `24h`
+    pub every: String,
+    /// Extra time before a late ping counts as missed, e.g. `1h`
+    #[serde(default)]
+    pub grace: Option<String>,
+}
+
+/// A health check, probed every `interval`
+#[derive(Deserialize, Debug)]
+pub struct HealthCheckConfig {
+    pub name: String,
+    #[serde(default = "default_check_interval")]
+    pub interval: String,
+    #[serde(default = "default_check_timeout")]
+    pub timeout: String,
+    #[serde(flatten)]
+    pub probe: ProbeConfig,
+}
+
+fn default_check_interval() -> String {
+    "30s".to_string()
+}
+
+fn default_check_timeout() -> String {
+    "5s".to_string()
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProbeConfig {
+    /// GET `url`, passing when the status matches and the body matches the regex
+    Http {
+        url: String,
+        #[serde(default = "default_expect_status")]
+        expect_status: u16,
+        #[serde(default)]
+        expect_body: Option<String>,
+    },
+    /// Connects to `address`, e.g. `localhost:5432`
+    Tcp {
+        address: String,
+    },
+    Unix {
+        path: String,
+    },
+    /// Passes when the command exits with 0, run through `sh -c`
+    Command {
+        command: String,
+    },
+}
+
+fn default_expect_status() -> u16 {
+    200
+}
+
+/// An executable run every `interval` through `sh -c`, printing a JSON object
+/// per line, either `{"metric": "ups.charge", "value": 97, "unit": "percent"}` or
+/// `{"status": "tank", "state": "warning", "description": "degraded"}`
+#[derive(Deserialize, Debug)]
+pub struct PluginConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default = "default_plugin_interval")]
+    pub interval: String,
+    /// Runs taking longer are killed and count as failed
+    #[serde(default = "default_plugin_timeout")]
+    pub timeout: String,
+}
+
+fn default_plugin_interval() -> String {
+    "1m".to_string()
+}
+
+fn default_plugin_timeout() -> String {
+    "10s".to_string()
+}
+
+/// A Rhai script run in-process without access to anything but its API. It can
+/// define `init()`, `on_event(msg)` for messages on `topics` and `on_tick()`
+/// for every `interval`, which all share their state through `this`
+#[derive(Deserialize, Debug)]
+pub struct ScriptConfig {
+    pub name: String,
+    pub path: String,
+    /// Not `*` or what scripts publish on themselves
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub interval: Option<String>,
+    /// The only files `read_file` may read
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Calls taking longer are stopped and count as failed
+    #[serde(default = "default_script_timeout")]
+    pub timeout: String,
+}
+
+fn default_script_timeout() -> String {
+    "1s".to_string()
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct CertConfig {
+    /// PEM or DER files, for bundles the certificate expiring first counts
+    pub paths: Vec<String>,
+    /// `host:port` endpoints to do a TLS handshake with
+    pub endpoints: Vec<String>,
+    pub warn_days: i64,
+    pub error_days: i64,
+    pub interval: String,
+}
+
+impl Default for CertConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec![],
+            endpoints: vec![],
+            warn_days: 30,
+            error_days: 7,
+            interval: "1h".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct LogConfig {
+    pub files: Vec<TailFileConfig>,
+    pub highlight: Vec<HighlightConfig>,
+    pub trigger: Vec<TriggerConfig>,
+    /// Lines kept for the log panel
+    pub lines: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            files: vec![],
+            highlight: vec![],
+            trigger: vec![],
+            lines: 500,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TailFileConfig {
+    pub path: String,
+    /// Shown next to each line, defaults to the file name
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Styles the parts of a line matching `pattern`, e.g. `style = "red bold"`
+#[derive(Deserialize, Debug)]
+pub struct HighlightConfig {
+    pub pattern: String,
+    pub style: String,
+}
+
+/// Raises a status when a line matches `pattern`, until a line matches `clear`
+#[derive(Deserialize, Debug)]
+pub struct TriggerConfig {
+    pub pattern: String,
+    #[serde(default)]
+    pub clear: Option<String>,
+    /// Only look at this file, by name
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Title of the status, defaults to `<file>: <pattern>`
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// The read-only HTTP and WebSocket API, off unless enabled
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub enabled: bool,
+    /// `address:port`, anything but localhost is reachable by others
+    pub listen: String,
+    /// Required as `Authorization: Bearer <token>`, or `?token=` for browsers
+    /// opening the WebSocket
+    pub token: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:8765".to_string(),
+            token: None,
+        }
+    }
+}
@@ -1,19 +1,43 @@
+use serde_json::Value;
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum EventFieldType {
     Description,
     Memory,
     Cpu,
     Timestamp,
+    State,
+    Error,
+    Uptime,
+    Interval,
+    Grace,
+    Latency,
+    Load,
+    NetworkRx,
+    NetworkTx,
+    Value,
+    Unit,
 }
 
 impl EventFieldType {
-    pub fn from_string(input: &str) -> Self {
+    pub fn from_string(input: &str) -> Option<Self> {
         match input.to_lowercase().as_str() {
-            "description" => EventFieldType::Description,
-            "memory" => EventFieldType::Memory,
-            "cpu" => EventFieldType::Cpu,
-            "timestamp" => EventFieldType::Timestamp,
-            &_ => panic!("invalid field type, got {input}"),
+            "description" => Some(EventFieldType::Description),
+            "memory" => Some(EventFieldType::Memory),
+            "cpu" => Some(EventFieldType::Cpu),
+            "timestamp" => Some(EventFieldType::Timestamp),
+            "state" => Some(EventFieldType::State),
+            "error" => Some(EventFieldType::Error),
+            "uptime" => Some(EventFieldType::Uptime),
+            "interval" => Some(EventFieldType::Interval),
+            "grace" => Some(EventFieldType::Grace),
+            "latency" => Some(EventFieldType::Latency),
+            "load" => Some(EventFieldType::Load),
+            "network_rx" => Some(EventFieldType::NetworkRx),
+            "network_tx" => Some(EventFieldType::NetworkTx),
+            "value" => Some(EventFieldType::Value),
+            "unit" => Some(EventFieldType::Unit),
+            &_ => None,
         }
     }
 
@@ -23,6 +47,42 @@ impl EventFieldType {
             EventFieldType::Memory => "memory",
             EventFieldType::Cpu => "cpu",
             EventFieldType::Timestamp => "timestamp",
+            EventFieldType::State => "state",
+            EventFieldType::Error => "error",
+            EventFieldType::Uptime => "uptime",
+            EventFieldType::Interval => "interval",
+            EventFieldType::Grace => "grace",
+            EventFieldType::Latency => "latency",
+            EventFieldType::Load => "load",
+            EventFieldType::NetworkRx => "network_rx",
+            EventFieldType::NetworkTx => "network_tx",
+            EventFieldType::Value => "value",
+            EventFieldType::Unit => "unit",
+        }
+    }
+
+    /// Numbers are published as little endian bytes, everything else as UTF-8
+    pub fn decode(&self, bytes: &[u8]) -> Value {
+        let Ok(raw) = <[u8; 8]>::try_from(bytes) else {
+            return Value::from(String::from_utf8_lossy(bytes).to_string());
+        };
+        match self {
+            EventFieldType::Description
+            | EventFieldType::State
+            | EventFieldType::Error
+            | EventFieldType::Unit => Value::from(String::from_utf8_lossy(bytes).to_string()),
+            EventFieldType::Timestamp | EventFieldType::Interval | EventFieldType::Grace => {
+                Value::from(i64::from_le_bytes(raw))
+            }
+            EventFieldType::Uptime | EventFieldType::Latency => {
+                Value::from(u64::from_le_bytes(raw))
+            }
+            EventFieldType::Memory
+            | EventFieldType::Cpu
+            | EventFieldType::Load
+            | EventFieldType::NetworkRx
+            | EventFieldType::NetworkTx
+            | EventFieldType::Value => Value::from(f64::from_le_bytes(raw)),
         }
     }
 }
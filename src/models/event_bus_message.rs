@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use serde_json::{Map, Value};
+
 use super::{event_bus_field_type::EventFieldType, event_type::EventType};
 
 const MIN_MESSAGE_LENGTH: usize = 2;
@@ -32,41 +34,41 @@ impl EventBusMessage {
     }
 
     pub fn from_bytes(data: Vec<u8>) -> Self {
-        let binding = String::from_utf8(data).expect("unable to decode data");
+        match EventBusMessage::parse(&data) {
+            Some(msg) => msg,
+            None => panic!("invalid message, got {}", String::from_utf8_lossy(&data)),
+        }
+    }
+
+    /// Like `from_bytes`, but None for anything that isn't a message instead of
+    /// panicking. For subscribers that see topics they don't know the payloads of
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let binding = std::str::from_utf8(data).ok()?;
         let mut decoded = binding.split('|').collect::<Vec<&str>>();
 
         if decoded.len() < MIN_MESSAGE_LENGTH {
-            panic!("invalid message, got {binding}");
+            return None;
         }
 
-        let event_type = match decoded[1].parse::<u8>() {
-            Ok(res) => EventType::from_u8(res),
-            Err(_) => panic!("unable to parse EventType, got {}", decoded[1]),
-        };
-
-        let fields = decoded.split_off(2).iter().fold(
-            HashMap::new(),
-            |mut acc: HashMap<EventFieldType, Vec<u8>>, elem| {
-                let split = elem
-                    .replace(" ", "")
-                    .split('=')
-                    .map(|elem| elem.to_string())
-                    .collect::<Vec<String>>();
-
-                acc.insert(
-                    EventFieldType::from_string(&split[0]),
-                    EventBusMessage::string_to_bytes(split[1].clone()),
-                );
-                acc
-            },
-        );
+        let event_type = EventType::from_u8(decoded[1].parse::<u8>().ok()?)?;
+
+        // a message without fields leaves a single empty segment behind
+        let mut fields = HashMap::new();
+        for elem in decoded.split_off(2).iter().filter(|elem| !elem.is_empty()) {
+            let elem = elem.replace(" ", "");
+            let (field, value) = elem.split_once('=')?;
+            fields.insert(
+                EventFieldType::from_string(field)?,
+                EventBusMessage::string_to_bytes(value.to_string()),
+            );
+        }
 
-        Self {
+        Some(Self {
             title: String::from(decoded[0]),
             event_type,
             fields,
             timestamp: chrono::Utc::now().timestamp(),
-        }
+        })
     }
 
     pub fn format(&self) -> String {
@@ -107,6 +109,10 @@ impl EventBusMessage {
             .clone()
     }
 
+    pub fn has_field(&self, key: EventFieldType) -> bool {
+        self.fields.contains_key(&key)
+    }
+
     pub fn get_field_string(&self, key: EventFieldType) -> String {
         String::from_utf8(self.get_field(key)).unwrap()
     }
@@ -119,6 +125,22 @@ impl EventBusMessage {
         self.timestamp
     }
 
+    /// For clients outside the process, with the fields decoded to what they
+    /// were published as
+    pub fn to_json(&self) -> Value {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(field, bytes)| (field.to_string().to_string(), field.decode(bytes)))
+            .collect::<Map<String, Value>>();
+        serde_json::json!({
+            "title": self.title,
+            "type": self.event_type.name(),
+            "timestamp": self.timestamp,
+            "fields": fields,
+        })
+    }
+
     fn create_fields(
         fields: Vec<(EventFieldType, Vec<u8>)>,
     ) -> Option<HashMap<EventFieldType, Vec<u8>>> {
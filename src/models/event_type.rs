@@ -6,16 +6,32 @@ pub enum EventType {
     Process = 1,
     HWusage = 2,
     Timestamp = 3,
+    Service = 4,
+    Maintenance = 5,
+    Heartbeat = 6,
+    HealthCheck = 7,
+    Certificate = 8,
+    LogLine = 9,
+    Plugin = 10,
+    Script = 11,
 }
 
 impl EventType {
-    pub fn from_u8(data: u8) -> Self {
+    pub fn from_u8(data: u8) -> Option<Self> {
         match data {
-            0 => EventType::Socket,
-            1 => EventType::Process,
-            2 => EventType::HWusage,
-            3 => EventType::Timestamp,
-            4_u8..=u8::MAX => panic!("invalid event"),
+            0 => Some(EventType::Socket),
+            1 => Some(EventType::Process),
+            2 => Some(EventType::HWusage),
+            3 => Some(EventType::Timestamp),
+            4 => Some(EventType::Service),
+            5 => Some(EventType::Maintenance),
+            6 => Some(EventType::Heartbeat),
+            7 => Some(EventType::HealthCheck),
+            8 => Some(EventType::Certificate),
+            9 => Some(EventType::LogLine),
+            10 => Some(EventType::Plugin),
+            11 => Some(EventType::Script),
+            12_u8..=u8::MAX => None,
         }
     }
 
@@ -25,6 +41,31 @@ impl EventType {
             EventType::Process => 1,
             EventType::HWusage => 2,
             EventType::Timestamp => 3,
+            EventType::Service => 4,
+            EventType::Maintenance => 5,
+            EventType::Heartbeat => 6,
+            EventType::HealthCheck => 7,
+            EventType::Certificate => 8,
+            EventType::LogLine => 9,
+            EventType::Plugin => 10,
+            EventType::Script => 11,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            EventType::Socket => "socket",
+            EventType::Process => "process",
+            EventType::HWusage => "hw_usage",
+            EventType::Timestamp => "timestamp",
+            EventType::Service => "service",
+            EventType::Maintenance => "maintenance",
+            EventType::Heartbeat => "heartbeat",
+            EventType::HealthCheck => "check",
+            EventType::Certificate => "certificate",
+            EventType::LogLine => "log",
+            EventType::Plugin => "plugin",
+            EventType::Script => "script",
         }
     }
 }
@@ -0,0 +1,17 @@
+/// Timing of a running socket job, compared against its earlier runs
+#[derive(Clone, Debug)]
+pub struct JobProgress {
+    pub elapsed: i64,
+    /// Median duration of the last runs with the same title, if there were any
+    pub expected: Option<i64>,
+    pub overdue: bool,
+}
+
+impl JobProgress {
+    /// Seconds left until the job usually finishes
+    pub fn eta(&self) -> Option<i64> {
+        self.expected
+            .map(|expected| expected - self.elapsed)
+            .filter(|left| *left > 0)
+    }
+}
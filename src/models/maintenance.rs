@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceMode {
+    /// Hide status entries while the window is open
+    #[default]
+    Suppress,
+    /// Keep showing status entries, but greyed out
+    Downgrade,
+}
+
+impl MaintenanceMode {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "suppress" => MaintenanceMode::Suppress,
+            "downgrade" => MaintenanceMode::Downgrade,
+            &_ => panic!("invalid maintenance mode, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            MaintenanceMode::Suppress => "suppress",
+            MaintenanceMode::Downgrade => "downgrade",
+        }
+    }
+}
+
+/// An open maintenance window, as seen by the widgets
+#[derive(Clone, Debug)]
+pub struct MaintenanceWindow {
+    pub until: i64,
+    pub reason: String,
+    pub mode: MaintenanceMode,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+/// How a panel draws its series, set per panel under `[charts]`
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricStyle {
+    /// Braille line chart against a time axis
+    #[default]
+    Line,
+    /// One row per series, a column per sample
+    Sparkline,
+    /// The current value of each series
+    Gauge,
+    /// A bar per sample, series side by side
+    Bar,
+    /// One row per series, shaded by value
+    Heatmap,
+}
@@ -1,4 +1,20 @@
+pub mod cert_state;
+pub mod check_state;
+pub mod config;
 pub mod event_bus_field_type;
 pub mod event_bus_message;
 pub mod event_type;
+pub mod job;
+pub mod maintenance;
+pub mod metric_style;
+pub mod plugin_state;
+pub mod recorded_event;
+pub mod service_state;
+pub mod severity;
+pub mod snapshot;
 pub mod socket_message;
+pub mod status_action;
+pub mod status_event;
+pub mod status_group;
+pub mod status_order;
+pub mod time_series;
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+/// How a status reported by a plugin is doing, only `ok` keeps it off the
+/// status panel
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginState {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl PluginState {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "ok" => PluginState::Ok,
+            "warning" => PluginState::Warning,
+            "critical" => PluginState::Critical,
+            &_ => panic!("invalid plugin state, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            PluginState::Ok => "ok",
+            PluginState::Warning => "warning",
+            PluginState::Critical => "critical",
+        }
+    }
+}
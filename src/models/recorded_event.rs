@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// A publish on the event bus, recordings are a JSON line for each
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecordedEvent {
+    /// Milliseconds since the epoch
+    pub ts: i64,
+    pub topic: String,
+    /// Messages are formatted as text, see `EventBusMessage::format`
+    pub payload: String,
+}
@@ -0,0 +1,26 @@
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ServiceState {
+    Running,
+    /// Crashed or exited on its own, waiting for the backoff before starting again
+    Restarting,
+    Stopped,
+}
+
+impl ServiceState {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "running" => ServiceState::Running,
+            "restarting" => ServiceState::Restarting,
+            "stopped" => ServiceState::Stopped,
+            &_ => panic!("invalid service state, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            ServiceState::Running => "running",
+            ServiceState::Restarting => "restarting",
+            ServiceState::Stopped => "stopped",
+        }
+    }
+}
@@ -0,0 +1,17 @@
+/// How bad a status entry is, most severe first so sorting puts it on top
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Critical,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn to_string(&self) -> &str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use super::time_series::TimeSeries;
+
+/// What a snapshot of the dashboard is written as
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFormat {
+    /// The screen as plain text
+    Text,
+    /// Statuses, metrics and containers, without any layout
+    Json,
+    /// The screen as a self-contained page, colours included
+    Html,
+}
+
+impl SnapshotFormat {
+    pub fn from_string(input: &str) -> Self {
+        match input {
+            "text" => SnapshotFormat::Text,
+            "json" => SnapshotFormat::Json,
+            "html" => SnapshotFormat::Html,
+            _ => panic!("unknown snapshot format: {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            SnapshotFormat::Text => "text",
+            SnapshotFormat::Json => "json",
+            SnapshotFormat::Html => "html",
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            SnapshotFormat::Text => "txt",
+            SnapshotFormat::Json => "json",
+            SnapshotFormat::Html => "html",
+        }
+    }
+}
+
+/// The state behind the dashboard, as written by `SnapshotFormat::Json`
+#[derive(Serialize, Debug)]
+pub struct Snapshot {
+    /// RFC 3339, local time
+    pub taken: String,
+    pub statuses: Vec<StatusSnapshot>,
+    /// Active entries hidden by a snooze
+    pub snoozed: usize,
+    pub metrics: Vec<MetricSnapshot>,
+    pub containers: ContainerSnapshot,
+    pub services: Vec<ServiceSnapshot>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StatusSnapshot {
+    pub title: String,
+    pub description: String,
+    pub severity: String,
+    /// `active` or `acknowledged`
+    pub state: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub group: String,
+    /// Unix timestamp of when it was first seen
+    pub since: i64,
+}
+
+/// Current, min, max and average over the window a panel shows
+#[derive(Serialize, Debug)]
+pub struct MetricSnapshot {
+    pub name: String,
+    pub unit: String,
+    pub current: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+impl MetricSnapshot {
+    /// None until the series has a sample
+    pub fn from_series(series: &TimeSeries) -> Option<Self> {
+        let stats = series.stats()?;
+        Some(Self {
+            name: series.name.clone(),
+            unit: series.unit.to_string().to_string(),
+            current: stats.current,
+            min: stats.min,
+            max: stats.max,
+            avg: stats.avg,
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ContainerSnapshot {
+    pub all: usize,
+    pub running: usize,
+    pub failed: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ServiceSnapshot {
+    pub name: String,
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -1,7 +1,93 @@
 use serde::{Deserialize, Serialize};
 
+use super::{maintenance::MaintenanceMode, snapshot::SnapshotFormat};
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SocketMessage {
     pub title: String,
     pub status: String,
 }
+
+/// A ping from a periodic job, `every` and `grace` register the heartbeat if it
+/// isn't in the config
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeartbeatMessage {
+    pub heartbeat: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace: Option<String>,
+}
+
+/// Anything that isn't a plain status update, tagged by `command`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "command")]
+pub enum SocketCommand {
+    #[serde(rename = "maintenance.start")]
+    MaintenanceStart {
+        /// e.g. `2h` or `1h30m`
+        duration: String,
+        #[serde(default)]
+        reason: String,
+        #[serde(default)]
+        mode: MaintenanceMode,
+    },
+    #[serde(rename = "maintenance.end")]
+    MaintenanceEnd,
+    #[serde(rename = "status.ack")]
+    StatusAck { title: String },
+    #[serde(rename = "status.dismiss")]
+    StatusDismiss { title: String },
+    #[serde(rename = "status.snooze")]
+    StatusSnooze {
+        title: String,
+        /// e.g. `30m`, defaults to 15 minutes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration: Option<String>,
+    },
+    /// Written by the running instance, so `path` must be absolute and new
+    #[serde(rename = "snapshot")]
+    Snapshot {
+        format: SnapshotFormat,
+        path: String,
+    },
+}
+
+/// Read requests, answered with a line of JSON on the same connection
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "command")]
+pub enum SocketQuery {
+    /// Active entries in the order the status panel shows them
+    #[serde(rename = "status.list")]
+    StatusList,
+    /// Current, min, max and average of every series the dashboard keeps
+    #[serde(rename = "metrics.current")]
+    MetricsCurrent,
+    /// Samples of one series by name, e.g. `cpu` or a health check, going back
+    /// `since` (e.g. `5m`) or as far as the dashboard keeps them
+    #[serde(rename = "metrics.range")]
+    MetricsRange {
+        metric: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<String>,
+    },
+    /// Container counts as on the podman panel
+    #[serde(rename = "containers.list")]
+    ContainersList,
+    /// Processes given on the command line, and whether they were last seen
+    #[serde(rename = "processes.watched")]
+    ProcessesWatched,
+    /// Streams every message published on `topic` as a line of JSON until the
+    /// client disconnects, `*` gets all of them
+    #[serde(rename = "subscribe")]
+    Subscribe { topic: String },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum SocketRequest {
+    Command(SocketCommand),
+    Query(SocketQuery),
+    Heartbeat(HeartbeatMessage),
+    Status(SocketMessage),
+}
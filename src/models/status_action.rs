@@ -0,0 +1,29 @@
+/// What a user can do with a status entry, from the UI or over the socket
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum StatusAction {
+    /// Keep showing the entry, but greyed out
+    Acknowledge,
+    /// Remove the entry until it's reported with a new description
+    Dismiss,
+    /// Hide the entry for a while, it comes back on its own
+    Snooze,
+}
+
+impl StatusAction {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "acknowledge" => StatusAction::Acknowledge,
+            "dismiss" => StatusAction::Dismiss,
+            "snooze" => StatusAction::Snooze,
+            &_ => panic!("invalid status action, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            StatusAction::Acknowledge => "acknowledge",
+            StatusAction::Dismiss => "dismiss",
+            StatusAction::Snooze => "snooze",
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusTransition {
+    Started,
+    Updated,
+    Done,
+    /// Reported a `failed` status, stays on the panel until it's replaced
+    Failed,
+    /// Wasn't refreshed within the cleanup interval
+    Expired,
+    Acknowledged,
+    /// Removed by hand
+    Dismissed,
+    Snoozed,
+}
+
+impl StatusTransition {
+    pub fn to_string(&self) -> &str {
+        match self {
+            StatusTransition::Started => "started",
+            StatusTransition::Updated => "updated",
+            StatusTransition::Done => "done",
+            StatusTransition::Failed => "failed",
+            StatusTransition::Expired => "expired",
+            StatusTransition::Acknowledged => "acknowledged",
+            StatusTransition::Dismissed => "dismissed",
+            StatusTransition::Snoozed => "snoozed",
+        }
+    }
+}
+
+/// A single entry in the status timeline
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatusEvent {
+    pub ts: i64,
+    pub title: String,
+    /// Name of the `EventType` the status came from, e.g. `socket`
+    pub source: String,
+    pub transition: StatusTransition,
+    pub description: String,
+    /// Seconds since the status started, only set when it closed
+    pub duration: Option<i64>,
+}
@@ -0,0 +1,33 @@
+use super::event_type::EventType;
+
+/// Where a status entry came from, the status table is grouped by this
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum StatusGroup {
+    /// Heartbeats, health checks, certificates, log triggers, plugins and scripts
+    Alert,
+    Socket,
+    Process,
+}
+
+impl StatusGroup {
+    pub fn from_event_type(event_type: &EventType) -> Self {
+        match event_type {
+            EventType::Socket => StatusGroup::Socket,
+            EventType::Heartbeat
+            | EventType::HealthCheck
+            | EventType::Certificate
+            | EventType::LogLine
+            | EventType::Plugin
+            | EventType::Script => StatusGroup::Alert,
+            _ => StatusGroup::Process,
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            StatusGroup::Alert => "alert",
+            StatusGroup::Socket => "socket",
+            StatusGroup::Process => "process",
+        }
+    }
+}
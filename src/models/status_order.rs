@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+/// What status entries can be sorted by, `[status] order` lists them by priority
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Most severe first
+    Severity,
+    /// Oldest first
+    Since,
+    Title,
+}
+
+impl SortKey {
+    /// The order the status table cycles through
+    pub fn next(&self) -> Self {
+        match self {
+            SortKey::Severity => SortKey::Since,
+            SortKey::Since => SortKey::Title,
+            SortKey::Title => SortKey::Severity,
+        }
+    }
+}
+
+/// How status entries are grouped, pinned entries always come first
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// One group per event type, e.g. `socket` or `heartbeat`
+    #[default]
+    Type,
+    /// Sockets, processes and alerts
+    Source,
+    None,
+}
@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+
+/// How a series' values are shown on axes and in legends
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    /// 0 to 100, charts keep a fixed axis for these
+    Percent,
+    Plain,
+    BytesPerSec,
+    Millis,
+}
+
+impl Unit {
+    pub fn from_string(input: &str) -> Self {
+        match input.to_lowercase().as_str() {
+            "percent" => Unit::Percent,
+            "plain" => Unit::Plain,
+            "bytes_per_sec" => Unit::BytesPerSec,
+            "millis" => Unit::Millis,
+            &_ => panic!("invalid unit, got {input}"),
+        }
+    }
+
+    pub fn to_string(&self) -> &str {
+        match self {
+            Unit::Percent => "percent",
+            Unit::Plain => "plain",
+            Unit::BytesPerSec => "bytes_per_sec",
+            Unit::Millis => "millis",
+        }
+    }
+
+    /// Smallest top of an auto-scaled axis, so idle series don't blow up noise
+    pub fn min_axis(&self) -> f64 {
+        match self {
+            Unit::Percent => 100.0,
+            Unit::Plain => 1.0,
+            Unit::BytesPerSec => 1000.0,
+            Unit::Millis => 100.0,
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Unit::Percent => format!("{value:.0}%"),
+            Unit::Plain => format!("{value:.2}"),
+            Unit::Millis => format!("{value:.0}ms"),
+            Unit::BytesPerSec => {
+                let units = ["B/s", "kB/s", "MB/s", "GB/s"];
+                let mut value = value;
+                let mut unit = 0;
+                while value >= 1000.0 && unit < units.len() - 1 {
+                    value /= 1000.0;
+                    unit += 1;
+                }
+                if unit == 0 {
+                    format!("{value:.0} {}", units[unit])
+                } else {
+                    format!("{value:.1} {}", units[unit])
+                }
+            }
+        }
+    }
+}
+
+/// Current, min, max and average of what's in a series' window
+#[derive(Clone, Copy, Debug)]
+pub struct SeriesStats {
+    pub current: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// Timestamped samples of one metric, anything older than `window` seconds is
+/// dropped as new samples come in
+#[derive(Clone, Debug)]
+pub struct TimeSeries {
+    pub name: String,
+    pub unit: Unit,
+    /// Seconds since the epoch, with millisecond precision, and the value
+    samples: VecDeque<(f64, f64)>,
+    window: f64,
+}
+
+impl TimeSeries {
+    pub fn new(name: &str, unit: Unit, window: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            unit,
+            samples: VecDeque::new(),
+            window: window as f64,
+        }
+    }
+
+    pub fn push(&mut self, ts: f64, value: f64) {
+        self.samples.push_back((ts, value));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(oldest, _)| ts - oldest > self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn window(&self) -> f64 {
+        self.window
+    }
+
+    /// Points with x in seconds relative to `now`, so the newest is close to 0
+    pub fn relative_to(&self, now: f64) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .map(|(ts, value)| (ts - now, *value))
+            .collect()
+    }
+
+    /// Samples taken at or after `ts`, oldest first
+    pub fn since(&self, ts: f64) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .filter(|(sample_ts, _)| *sample_ts >= ts)
+            .copied()
+            .collect()
+    }
+
+    /// Values of the newest `count` samples, oldest first
+    pub fn last(&self, count: usize) -> Vec<f64> {
+        let skip = self.samples.len().saturating_sub(count);
+        self.samples
+            .iter()
+            .skip(skip)
+            .map(|(_, value)| *value)
+            .collect()
+    }
+
+    pub fn stats(&self) -> Option<SeriesStats> {
+        let (_, current) = self.samples.back()?;
+        let mut stats = SeriesStats {
+            current: *current,
+            min: f64::MAX,
+            max: f64::MIN,
+            avg: 0.0,
+        };
+        for (_, value) in &self.samples {
+            stats.min = stats.min.min(*value);
+            stats.max = stats.max.max(*value);
+            stats.avg += value;
+        }
+        stats.avg /= self.samples.len() as f64;
+        Some(stats)
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::trace;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use x509_parser::{certificate::X509Certificate, pem::Pem};
+
+use crate::{
+    api::tls,
+    models::{
+        cert_state::CertState, config::CertConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage, event_type::EventType,
+    },
+    traits::runnable::Runnable,
+    utils::{duration::parse_duration, task::cancellable_sleep},
+};
+
+use super::event_bus::EventBus;
+
+/// One message per certificate file or endpoint, titled with its path or address
+pub const EVENT_TOPIC: &str = "certs";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum Source {
+    File(String),
+    Endpoint(String),
+}
+
+impl Source {
+    fn label(&self) -> &str {
+        match self {
+            Source::File(path) => path,
+            Source::Endpoint(address) => address,
+        }
+    }
+}
+
+struct Expiry {
+    not_after: i64,
+    common_name: String,
+}
+
+struct Thresholds {
+    warn_days: i64,
+    error_days: i64,
+}
+
+/// Reads certificates from files and TLS endpoints and publishes how long they
+/// have left, flagged once they get within the configured thresholds
+pub struct CertService {
+    event_bus: Arc<Mutex<EventBus>>,
+    sources: Arc<Vec<Source>>,
+    thresholds: Arc<Thresholds>,
+    interval: Duration,
+}
+
+impl CertService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &CertConfig) -> io::Result<Self> {
+        let interval = parse_duration(&config.interval)
+            .filter(|secs| *secs > 0)
+            .ok_or(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid certificate interval '{}'", config.interval),
+            ))?;
+        if config.error_days > config.warn_days {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "certificate error_days must not be above warn_days",
+            ));
+        }
+
+        let sources = config
+            .paths
+            .iter()
+            .map(|path| Source::File(path.clone()))
+            .chain(
+                config
+                    .endpoints
+                    .iter()
+                    .map(|address| Source::Endpoint(address.clone())),
+            )
+            .collect::<Vec<Source>>();
+        // labels are the status titles
+        if let Some(source) = sources.iter().find(|source| source.label().contains('|')) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid certificate source '{}'", source.label()),
+            ));
+        }
+
+        Ok(Self {
+            event_bus,
+            sources: Arc::new(sources),
+            thresholds: Arc::new(Thresholds {
+                warn_days: config.warn_days,
+                error_days: config.error_days,
+            }),
+            interval: Duration::from_secs(interval),
+        })
+    }
+
+    async fn read_expiry(source: &Source) -> Result<Expiry, String> {
+        match source {
+            Source::File(path) => {
+                let data = tokio::fs::read(path).await.map_err(|err| err.to_string())?;
+                CertService::parse_file(&data)
+            }
+            Source::Endpoint(address) => {
+                let der = tokio::time::timeout(HANDSHAKE_TIMEOUT, tls::peer_certificate(address))
+                    .await
+                    .map_err(|_| "handshake timed out".to_string())?
+                    .map_err(|err| err.to_string())?;
+                let (_, cert) = x509_parser::parse_x509_certificate(&der)
+                    .map_err(|err| format!("invalid certificate: {err}"))?;
+                Ok(CertService::expiry(&cert))
+            }
+        }
+    }
+
+    /// PEM bundles count as expiring when their earliest certificate does
+    fn parse_file(data: &[u8]) -> Result<Expiry, String> {
+        if !data.starts_with(b"-----BEGIN") {
+            let (_, cert) = x509_parser::parse_x509_certificate(data)
+                .map_err(|err| format!("invalid certificate: {err}"))?;
+            return Ok(CertService::expiry(&cert));
+        }
+
+        let mut earliest: Option<Expiry> = None;
+        for pem in Pem::iter_from_buffer(data) {
+            let pem = pem.map_err(|err| format!("invalid PEM: {err}"))?;
+            if pem.label != "CERTIFICATE" {
+                continue;
+            }
+            let cert = pem
+                .parse_x509()
+                .map_err(|err| format!("invalid certificate: {err}"))?;
+            let expiry = CertService::expiry(&cert);
+            if earliest
+                .as_ref()
+                .is_none_or(|earliest| expiry.not_after < earliest.not_after)
+            {
+                earliest = Some(expiry);
+            }
+        }
+
+        earliest.ok_or("no certificate in file".to_string())
+    }
+
+    fn expiry(cert: &X509Certificate) -> Expiry {
+        Expiry {
+            not_after: cert.validity().not_after.timestamp(),
+            common_name: cert
+                .subject()
+                .iter_common_name()
+                .next()
+                .and_then(|cn| cn.as_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    fn describe(expiry: &Expiry, days_left: i64) -> String {
+        let when = match days_left {
+            days if days < 0 => format!("expired {} days ago", -days),
+            0 => "expires today".to_string(),
+            1 => "expires tomorrow".to_string(),
+            days => format!("expires in {days} days"),
+        };
+        if expiry.common_name.is_empty() {
+            when
+        } else {
+            format!("{}, {when}", expiry.common_name)
+        }
+    }
+
+    async fn check(source: &Source, thresholds: &Thresholds, event_bus: &Mutex<EventBus>) {
+        let result = CertService::read_expiry(source).await;
+        let mut fields = vec![];
+
+        let (state, description) = match result {
+            Ok(expiry) => {
+                let days_left =
+                    (expiry.not_after - chrono::Utc::now().timestamp()).div_euclid(86_400);
+                let state = if days_left <= thresholds.error_days {
+                    CertState::Critical
+                } else if days_left <= thresholds.warn_days {
+                    CertState::Expiring
+                } else {
+                    CertState::Valid
+                };
+                fields.push((
+                    EventFieldType::Timestamp,
+                    expiry.not_after.to_le_bytes().to_vec(),
+                ));
+                (state, CertService::describe(&expiry, days_left))
+            }
+            // can't tell when it expires, which is just as bad
+            Err(err) => (CertState::Critical, err),
+        };
+        trace!("certificate '{}': {description}", source.label());
+
+        fields.push((EventFieldType::State, state.to_string().as_bytes().to_vec()));
+        fields.push((EventFieldType::Description, description.into_bytes()));
+        event_bus.lock().unwrap().publish(
+            EVENT_TOPIC,
+            EventBusMessage::new(source.label(), EventType::Certificate, Some(fields))
+                .format_bytes(),
+        );
+    }
+}
+
+impl Runnable for CertService {
+    fn name(&self) -> &'static str {
+        "certs"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let sources = Arc::clone(&self.sources);
+        let thresholds = Arc::clone(&self.thresholds);
+        let interval = self.interval;
+
+        tokio::spawn(async move {
+            loop {
+                for source in sources.iter() {
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = CertService::check(source, &thresholds, &event_bus) => (),
+                    }
+                }
+                if cancellable_sleep(&cancel, interval).await {
+                    break;
+                }
+            }
+        })
+    }
+}
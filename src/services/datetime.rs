@@ -1,15 +1,19 @@
 use std::{
     sync::{Arc, Mutex},
-    thread::sleep,
     time::Duration,
 };
 
+use sysinfo::System;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
 use crate::{
     models::{
         event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
         event_type::EventType,
     },
     traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
 };
 
 pub const EVENT_TOPIC: &str = "datetime_timestamp";
@@ -27,12 +31,16 @@ impl DateTimeService {
 
     fn poll(event_bus: Arc<Mutex<EventBus>>) {
         let ts = chrono::Utc::now().timestamp().to_le_bytes().to_vec();
+        let uptime = System::uptime().to_le_bytes().to_vec();
         event_bus.lock().unwrap().publish(
             EVENT_TOPIC,
             EventBusMessage::new(
                 "timestamp",
                 EventType::Timestamp,
-                Some(vec![(EventFieldType::Timestamp, ts)]),
+                Some(vec![
+                    (EventFieldType::Timestamp, ts),
+                    (EventFieldType::Uptime, uptime),
+                ]),
             )
             .format_bytes(),
         )
@@ -40,14 +48,20 @@ impl DateTimeService {
 }
 
 impl Runnable for DateTimeService {
-    fn run(&self) {
+    fn name(&self) -> &'static str {
+        "datetime"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
         let event_bus = Arc::clone(&self.event_bus);
 
         tokio::spawn(async move {
             loop {
                 DateTimeService::poll(Arc::clone(&event_bus));
-                sleep(Duration::from_millis(1000));
+                if cancellable_sleep(&cancel, Duration::from_millis(1000)).await {
+                    break;
+                }
             }
-        });
+        })
     }
 }
@@ -1,42 +1,44 @@
 use std::collections::HashMap;
 
+/// Subscribers to this get every message, whatever topic it was published on
+pub const ANY_TOPIC: &str = "*";
+
 type Callback = dyn Fn(Vec<u8>) + Send + 'static;
+type Tap = dyn Fn(&str, &[u8]) + Send + 'static;
 
 pub struct EventBus {
     subscribers: HashMap<String, Vec<(i32, Box<Callback>)>>,
+    taps: Vec<Box<Tap>>,
+    /// Never handed out twice, so unsubscribing can't remove someone else's
+    /// callback that got a freed ID
+    next_id: i32,
 }
 
 impl EventBus {
     pub fn new() -> Self {
         Self {
             subscribers: HashMap::new(),
+            taps: vec![],
+            next_id: 0,
         }
     }
 
-    fn get_free_id(&self, event_type: &str) -> i32 {
-        let subscribers = match self.subscribers.get(event_type) {
-            Some(res) => res,
-            None => return 0,
-        };
-
-        // could this me more efficient? do we need to iterate through all IDs?
-        // use tokens instead?
-        let mut free_id: i32 = 0;
-        for (id, _) in subscribers {
-            if *id == free_id {
-                free_id += 1;
-            }
-        }
-
-        free_id
-    }
-
     pub fn publish(&self, event_type: &str, payload: Vec<u8>) {
+        for tap in &self.taps {
+            tap(event_type, &payload);
+        }
         if let Some(subs) = self.subscribers.get(event_type) {
             for (_, callback) in subs {
                 callback(payload.clone());
             }
         }
+        if event_type != ANY_TOPIC {
+            if let Some(subs) = self.subscribers.get(ANY_TOPIC) {
+                for (_, callback) in subs {
+                    callback(payload.clone());
+                }
+            }
+        }
     }
 
     /// Returns the assigned ID for the callback
@@ -49,12 +51,19 @@ impl EventBus {
             self.subscribers.insert(event_type.to_string(), vec![]);
         }
 
-        let free_id = self.get_free_id(event_type);
+        let id = self.next_id;
+        self.next_id += 1;
         self.subscribers
             .get_mut(event_type)
             .expect("no vec in HashMap, for some reason!!")
-            .push((free_id, Box::new(callback)));
-        free_id
+            .push((id, Box::new(callback)));
+        id
+    }
+
+    /// Sees every publish with its topic before the subscribers do, can't be
+    /// removed again. Used for recording
+    pub fn tap<F: Fn(&str, &[u8]) + Send + 'static>(&mut self, callback: F) {
+        self.taps.push(Box::new(callback));
     }
 
     /// Returns the callbacks ID if removed, None otherwise
@@ -0,0 +1,291 @@
+use std::{
+    fs::{self, File},
+    io::{self, ErrorKind, Read, Seek, SeekFrom},
+    os::unix::fs::MetadataExt,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::{info, warn};
+use regex::Regex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        config::LogConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage, event_type::EventType,
+    },
+    traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
+};
+
+use super::event_bus::EventBus;
+
+/// Every new line, titled with the file name
+pub const EVENT_TOPIC: &str = "file_tail";
+/// Statuses raised and cleared by triggers, the state is `raised` or `cleared`
+pub const TRIGGER_TOPIC: &str = "log_trigger";
+pub const TRIGGER_RAISED: &str = "raised";
+pub const TRIGGER_CLEARED: &str = "cleared";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Caps how much a single poll reads, a huge burst is picked up over a few polls
+const MAX_READ: u64 = 1024 * 1024;
+
+struct TailedFile {
+    name: String,
+    path: PathBuf,
+    file: Option<File>,
+    inode: u64,
+    offset: u64,
+    /// The end of the last read, if it didn't end in a newline
+    partial: String,
+    /// Files are followed from their end, only lines written after we start count
+    seen: bool,
+}
+
+impl TailedFile {
+    /// New complete lines since the last poll. A rotated file is read to its end
+    /// before switching to the new one, a truncated one is read from the start
+    fn poll(&mut self) -> Vec<String> {
+        let mut data = vec![];
+
+        let meta = match fs::metadata(&self.path) {
+            Ok(res) => res,
+            Err(_) => {
+                // anything showing up here from now on is new
+                self.seen = true;
+                // moved away without a replacement yet, finish what's left
+                if let Some(mut file) = self.file.take() {
+                    let _ = file.read_to_end(&mut data);
+                }
+                return self.split_lines(&data);
+            }
+        };
+
+        if self.file.is_some() && meta.ino() != self.inode {
+            if let Some(mut file) = self.file.take() {
+                let _ = file.read_to_end(&mut data);
+            }
+        }
+
+        if self.file.is_none() {
+            match File::open(&self.path) {
+                Ok(file) => {
+                    self.offset = if self.seen { 0 } else { meta.len() };
+                    self.inode = meta.ino();
+                    self.file = Some(file);
+                    self.seen = true;
+                }
+                Err(err) => {
+                    warn!("unable to open {}: {err}", self.path.display());
+                    return self.split_lines(&data);
+                }
+            }
+        }
+
+        if meta.len() < self.offset {
+            info!(
+                "{} was truncated, reading from the start",
+                self.path.display()
+            );
+            self.offset = 0;
+            self.partial.clear();
+        }
+
+        if let Some(file) = &mut self.file {
+            let read = file
+                .seek(SeekFrom::Start(self.offset))
+                .and_then(|_| file.take(MAX_READ).read_to_end(&mut data));
+            match read {
+                Ok(len) => self.offset += len as u64,
+                Err(err) => warn!("unable to read {}: {err}", self.path.display()),
+            }
+        }
+
+        self.split_lines(&data)
+    }
+
+    fn split_lines(&mut self, data: &[u8]) -> Vec<String> {
+        if data.is_empty() {
+            return vec![];
+        }
+
+        let text = std::mem::take(&mut self.partial) + String::from_utf8_lossy(data).as_ref();
+        let mut lines: Vec<String> = text.split('\n').map(|line| line.to_string()).collect();
+        // whatever follows the last newline isn't a complete line yet
+        self.partial = lines.pop().unwrap_or_default();
+        lines
+            .into_iter()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect()
+    }
+}
+
+struct Trigger {
+    pattern: Regex,
+    clear: Option<Regex>,
+    file: Option<String>,
+    title: Option<String>,
+}
+
+impl Trigger {
+    /// `|` separates event bus message parts, so it can't be in a title
+    fn title(&self, file: &str) -> String {
+        self.title
+            .clone()
+            .unwrap_or(format!("{file}: {}", self.pattern.as_str()))
+            .replace('|', "/")
+    }
+}
+
+/// Follows log files like `tail -F`, publishing every new line and raising or
+/// clearing statuses when lines match the configured triggers
+pub struct FileTailService {
+    event_bus: Arc<Mutex<EventBus>>,
+    files: Arc<Mutex<Vec<TailedFile>>>,
+    triggers: Arc<Vec<Trigger>>,
+}
+
+impl FileTailService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &LogConfig) -> io::Result<Self> {
+        let regex = |pattern: &str| {
+            Regex::new(pattern).map_err(|err| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid log trigger '{pattern}': {err}"),
+                )
+            })
+        };
+
+        let files = config
+            .files
+            .iter()
+            .map(|file| {
+                let path = PathBuf::from(&file.path);
+                TailedFile {
+                    name: file.name.clone().unwrap_or(
+                        path.file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or(file.path.clone()),
+                    ),
+                    path,
+                    file: None,
+                    inode: 0,
+                    offset: 0,
+                    partial: String::new(),
+                    seen: false,
+                }
+            })
+            .collect::<Vec<TailedFile>>();
+        // names are the titles of the lines published for the file
+        if let Some(file) = files.iter().find(|file| file.name.contains('|')) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid log file name '{}'", file.name),
+            ));
+        }
+
+        let triggers = config
+            .trigger
+            .iter()
+            .map(|trigger| {
+                Ok(Trigger {
+                    pattern: regex(&trigger.pattern)?,
+                    clear: trigger.clear.as_deref().map(regex).transpose()?,
+                    file: trigger.file.clone(),
+                    title: trigger.title.clone(),
+                })
+            })
+            .collect::<io::Result<Vec<Trigger>>>()?;
+
+        Ok(Self {
+            event_bus,
+            files: Arc::new(Mutex::new(files)),
+            triggers: Arc::new(triggers),
+        })
+    }
+
+    fn poll(event_bus: &Mutex<EventBus>, files: &Mutex<Vec<TailedFile>>, triggers: &[Trigger]) {
+        let mut messages = vec![];
+        for file in files.lock().unwrap().iter_mut() {
+            for line in file.poll() {
+                messages.extend(FileTailService::trigger_messages(
+                    &file.name, &line, triggers,
+                ));
+                messages.push((
+                    EVENT_TOPIC,
+                    EventBusMessage::new(
+                        &file.name,
+                        EventType::LogLine,
+                        Some(vec![(EventFieldType::Description, line.into_bytes())]),
+                    ),
+                ));
+            }
+        }
+
+        let lock = event_bus.lock().unwrap();
+        for (topic, msg) in messages {
+            lock.publish(topic, msg.format_bytes());
+        }
+    }
+
+    fn trigger_messages(
+        file: &str,
+        line: &str,
+        triggers: &[Trigger],
+    ) -> Vec<(&'static str, EventBusMessage)> {
+        triggers
+            .iter()
+            .filter(|trigger| trigger.file.as_ref().is_none_or(|name| name == file))
+            .filter_map(|trigger| {
+                let state = if trigger.pattern.is_match(line) {
+                    TRIGGER_RAISED
+                } else if trigger
+                    .clear
+                    .as_ref()
+                    .is_some_and(|clear| clear.is_match(line))
+                {
+                    TRIGGER_CLEARED
+                } else {
+                    return None;
+                };
+
+                Some((
+                    TRIGGER_TOPIC,
+                    EventBusMessage::new(
+                        &trigger.title(file),
+                        EventType::LogLine,
+                        Some(vec![
+                            (EventFieldType::State, state.as_bytes().to_vec()),
+                            (EventFieldType::Description, line.as_bytes().to_vec()),
+                        ]),
+                    ),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Runnable for FileTailService {
+    fn name(&self) -> &'static str {
+        "file_tail"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let files = Arc::clone(&self.files);
+        let triggers = Arc::clone(&self.triggers);
+
+        tokio::spawn(async move {
+            loop {
+                FileTailService::poll(&event_bus, &files, &triggers);
+                if cancellable_sleep(&cancel, POLL_INTERVAL).await {
+                    break;
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    io::{self, ErrorKind},
+    path::PathBuf,
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{info, trace};
+use regex::Regex;
+use tokio::{
+    net::{TcpStream, UnixStream},
+    process::Command,
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    api::http::{self, HttpUrl},
+    models::{
+        check_state::CheckState,
+        config::{HealthCheckConfig, ProbeConfig},
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        event_type::EventType,
+    },
+    traits::runnable::Runnable,
+    utils::{duration::parse_duration, task::cancellable_sleep},
+};
+
+use super::event_bus::EventBus;
+
+/// Every probe result, titled with the check name
+pub const EVENT_TOPIC: &str = "health_check";
+
+enum Probe {
+    Http {
+        url: HttpUrl,
+        expect_status: u16,
+        expect_body: Option<Regex>,
+    },
+    Tcp(String),
+    Unix(PathBuf),
+    Command(String),
+}
+
+struct HealthCheck {
+    name: String,
+    interval: Duration,
+    timeout: Duration,
+    probe: Probe,
+}
+
+/// Probes each configured check on its own interval and publishes whether it
+/// passed, with the latency and what went wrong
+pub struct HealthCheckService {
+    event_bus: Arc<Mutex<EventBus>>,
+    checks: Arc<Vec<HealthCheck>>,
+}
+
+impl HealthCheckService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &[HealthCheckConfig]) -> io::Result<Self> {
+        let checks = config
+            .iter()
+            .map(HealthCheckService::parse_check)
+            .collect::<Result<Vec<HealthCheck>, String>>()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+        Ok(Self {
+            event_bus,
+            checks: Arc::new(checks),
+        })
+    }
+
+    fn parse_check(config: &HealthCheckConfig) -> Result<HealthCheck, String> {
+        let duration = |value: &str| {
+            parse_duration(value)
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .ok_or(format!(
+                    "invalid duration '{value}' for check '{}'",
+                    config.name
+                ))
+        };
+        // the status title is the name
+        if config.name.is_empty() || config.name.contains('|') {
+            return Err(format!("invalid check name '{}'", config.name));
+        }
+
+        let probe = match &config.probe {
+            ProbeConfig::Http {
+                url,
+                expect_status,
+                expect_body,
+            } => Probe::Http {
+                url: HttpUrl::parse(url)?,
+                expect_status: *expect_status,
+                expect_body: expect_body
+                    .as_ref()
+                    .map(|pattern| Regex::new(pattern))
+                    .transpose()
+                    .map_err(|err| format!("invalid regex for check '{}': {err}", config.name))?,
+            },
+            ProbeConfig::Tcp { address } => Probe::Tcp(address.clone()),
+            ProbeConfig::Unix { path } => Probe::Unix(PathBuf::from(path)),
+            ProbeConfig::Command { command } => Probe::Command(command.clone()),
+        };
+
+        Ok(HealthCheck {
+            name: config.name.clone(),
+            interval: duration(&config.interval)?,
+            timeout: duration(&config.timeout)?,
+            probe,
+        })
+    }
+
+    /// Returns what was seen on success, or why the probe failed
+    async fn probe(probe: &Probe) -> Result<String, String> {
+        match probe {
+            Probe::Http {
+                url,
+                expect_status,
+                expect_body,
+            } => {
+                let response = http::get(url).await.map_err(|err| err.to_string())?;
+                if response.status != *expect_status {
+                    return Err(format!(
+                        "HTTP {}, expected {expect_status}",
+                        response.status
+                    ));
+                }
+                if let Some(regex) = expect_body {
+                    if !regex.is_match(&response.body) {
+                        return Err(format!("HTTP {}, body doesn't match", response.status));
+                    }
+                }
+                Ok(format!("HTTP {}", response.status))
+            }
+            Probe::Tcp(address) => TcpStream::connect(address)
+                .await
+                .map(|_| "connected".to_string())
+                .map_err(|err| err.to_string()),
+            Probe::Unix(path) => UnixStream::connect(path)
+                .await
+                .map(|_| "connected".to_string())
+                .map_err(|err| err.to_string()),
+            Probe::Command(command) => {
+                let status = Command::new("sh")
+                    .args(["-c", command])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    // a timed out probe drops the child, don't leave it running
+                    .kill_on_drop(true)
+                    .status()
+                    .await
+                    .map_err(|err| err.to_string())?;
+                match status.code() {
+                    Some(0) => Ok("exit 0".to_string()),
+                    Some(code) => Err(format!("exit {code}")),
+                    None => Err("killed by a signal".to_string()),
+                }
+            }
+        }
+    }
+
+    async fn run_check(check: &HealthCheck, event_bus: &Mutex<EventBus>) {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(check.timeout, Self::probe(&check.probe)).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {}s", check.timeout.as_secs())),
+        };
+        let latency = started.elapsed().as_millis() as u64;
+        trace!("check '{}': {:?} in {latency}ms", check.name, result);
+
+        let (state, detail) = match result {
+            Ok(detail) => (CheckState::Passing, detail),
+            Err(detail) => (CheckState::Failing, detail),
+        };
+
+        event_bus.lock().unwrap().publish(
+            EVENT_TOPIC,
+            EventBusMessage::new(
+                &check.name,
+                EventType::HealthCheck,
+                Some(vec![
+                    (EventFieldType::State, state.to_string().as_bytes().to_vec()),
+                    (EventFieldType::Description, detail.into_bytes()),
+                    (EventFieldType::Latency, latency.to_le_bytes().to_vec()),
+                ]),
+            )
+            .format_bytes(),
+        );
+    }
+}
+
+impl Runnable for HealthCheckService {
+    fn name(&self) -> &'static str {
+        "health_check"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let checks = Arc::clone(&self.checks);
+        info!("starting {} health checks", checks.len());
+
+        tokio::spawn(async move {
+            let mut tasks = vec![];
+            for i in 0..checks.len() {
+                let event_bus = Arc::clone(&event_bus);
+                let checks = Arc::clone(&checks);
+                let cancel = cancel.clone();
+
+                tasks.push(tokio::spawn(async move {
+                    let check = &checks[i];
+                    loop {
+                        tokio::select! {
+                            _ = cancel.cancelled() => break,
+                            _ = HealthCheckService::run_check(check, &event_bus) => (),
+                        }
+                        if cancellable_sleep(&cancel, check.interval).await {
+                            break;
+                        }
+                    }
+                }));
+            }
+
+            for task in tasks {
+                let _ = task.await;
+            }
+            // without any checks there's nothing to wait for, but returning
+            // early would have the supervisor restart us over and over
+            cancel.cancelled().await;
+        })
+    }
+}
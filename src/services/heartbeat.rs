@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{Local, TimeZone};
+use log::{info, trace, warn};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Notify, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        config::HeartbeatConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage, event_type::EventType,
+    },
+    traits::runnable::Runnable,
+    utils::{
+        bytes_helper::bytes_to_i64, duration::format_duration, duration::parse_duration, snapshot,
+    },
+};
+
+use super::event_bus::EventBus;
+
+/// Missed heartbeats, one message per heartbeat on every tick
+pub const EVENT_TOPIC: &str = "heartbeat";
+/// Pings from the socket, titled with the heartbeat name
+pub const REQUEST_TOPIC: &str = "heartbeat_request";
+pub const HEARTBEAT_FILE: &str = "heartbeats.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Heartbeat {
+    every: i64,
+    grace: i64,
+    last_ping: Option<i64>,
+    /// When we started waiting, so a heartbeat that never pinged still goes missed
+    since: i64,
+    /// Registered over the socket rather than in the config
+    registered: bool,
+}
+
+impl Heartbeat {
+    fn deadline(&self) -> i64 {
+        // durations come from the socket, a huge one just never misses
+        self.last_ping
+            .unwrap_or(self.since)
+            .saturating_add(self.every)
+            .saturating_add(self.grace)
+    }
+}
+
+type Heartbeats = Arc<Mutex<HashMap<String, Heartbeat>>>;
+
+/// Dead man's switch for periodic jobs, a heartbeat is missed when it doesn't
+/// get a ping within its interval plus grace. Pings survive restarts
+pub struct HeartbeatService {
+    event_bus: Arc<Mutex<EventBus>>,
+    heartbeats: Heartbeats,
+    path: Option<Arc<PathBuf>>,
+    /// Pings only mark the state as changed, the service task writes it so the
+    /// bus isn't kept waiting on the disk
+    unsaved: Arc<Notify>,
+}
+
+impl HeartbeatService {
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        config: &[HeartbeatConfig],
+        path: Option<PathBuf>,
+    ) -> io::Result<Self> {
+        let mut heartbeats: HashMap<String, Heartbeat> = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(res) => Some(res),
+                Err(err) => {
+                    warn!("ignoring bad heartbeat state: {err}");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        // heartbeats removed from the config are forgotten, socket ones are kept.
+        // Names with `|` would break the bus messages they're published in
+        heartbeats.retain(|name, heartbeat| {
+            !name.contains('|')
+                && (heartbeat.registered || config.iter().any(|entry| entry.name == *name))
+        });
+
+        let now = chrono::Utc::now().timestamp();
+        for entry in config {
+            if entry.name.contains('|') {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid heartbeat name '{}'", entry.name),
+                ));
+            }
+            let invalid = |value: &str| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid heartbeat duration '{value}' for '{}'", entry.name),
+                )
+            };
+            let parse = |value: &str| {
+                parse_duration(value)
+                    .and_then(|secs| i64::try_from(secs).ok())
+                    .ok_or(invalid(value))
+            };
+            let every = parse(&entry.every)?;
+            let grace = match &entry.grace {
+                Some(grace) => parse(grace)?,
+                None => 0,
+            };
+
+            let heartbeat = heartbeats.entry(entry.name.clone()).or_insert(Heartbeat {
+                every,
+                grace,
+                last_ping: None,
+                since: now,
+                registered: false,
+            });
+            heartbeat.every = every;
+            heartbeat.grace = grace;
+            heartbeat.registered = false;
+        }
+
+        if let Some(path) = &path {
+            HeartbeatService::save(path, &heartbeats);
+        }
+
+        let heartbeats = Arc::new(Mutex::new(heartbeats));
+        let unsaved = Arc::new(Notify::new());
+        HeartbeatService::subscribe(
+            Arc::clone(&event_bus),
+            Arc::clone(&heartbeats),
+            Arc::clone(&unsaved),
+        );
+
+        Ok(Self {
+            event_bus,
+            heartbeats,
+            path: path.map(Arc::new),
+            unsaved,
+        })
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, heartbeats: Heartbeats, unsaved: Arc<Notify>) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(REQUEST_TOPIC, move |data| {
+            HeartbeatService::on_ping(data, &heartbeats, &unsaved);
+        });
+    }
+
+    fn on_ping(data: Vec<u8>, heartbeats: &Mutex<HashMap<String, Heartbeat>>, unsaved: &Notify) {
+        let msg = EventBusMessage::from_bytes(data);
+        trace!("HeartbeatService: on_ping: {:?}", msg);
+
+        let mut lock = heartbeats.lock().unwrap();
+        let now = msg.ts();
+        let heartbeat = match lock.get_mut(msg.title()) {
+            Some(heartbeat) => heartbeat,
+            None if msg.has_field(EventFieldType::Interval) => {
+                info!("registered heartbeat '{}'", msg.title());
+                lock.entry(msg.title().to_string()).or_insert(Heartbeat {
+                    every: 0,
+                    grace: 0,
+                    last_ping: None,
+                    since: now,
+                    registered: true,
+                })
+            }
+            None => {
+                warn!("ping for unknown heartbeat '{}'", msg.title());
+                return;
+            }
+        };
+
+        // the config has the last word on heartbeats defined there
+        if heartbeat.registered {
+            if msg.has_field(EventFieldType::Interval) {
+                heartbeat.every = bytes_to_i64(msg.get_field(EventFieldType::Interval));
+            }
+            if msg.has_field(EventFieldType::Grace) {
+                heartbeat.grace = bytes_to_i64(msg.get_field(EventFieldType::Grace));
+            }
+        }
+        heartbeat.last_ping = Some(now);
+        unsaved.notify_one();
+    }
+
+    /// Through a temporary file, a crash halfway doesn't lose every heartbeat
+    fn save(path: &Path, heartbeats: &HashMap<String, Heartbeat>) {
+        let written = serde_json::to_string(heartbeats)
+            .map_err(io::Error::from)
+            .and_then(|contents| snapshot::write(path, &contents));
+        if let Err(err) = written {
+            warn!("unable to save heartbeats to {}: {err}", path.display());
+        }
+    }
+
+    fn publish_missed(event_bus: &Mutex<EventBus>, heartbeats: &Mutex<HashMap<String, Heartbeat>>) {
+        let now = chrono::Utc::now().timestamp();
+        let missed = heartbeats
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, heartbeat)| heartbeat.deadline() < now)
+            .map(|(name, heartbeat)| (name.clone(), HeartbeatService::describe(heartbeat)))
+            .collect::<Vec<(String, String)>>();
+
+        let lock = event_bus.lock().unwrap();
+        for (name, description) in missed {
+            lock.publish(
+                EVENT_TOPIC,
+                EventBusMessage::new(
+                    &name,
+                    EventType::Heartbeat,
+                    Some(vec![(
+                        EventFieldType::Description,
+                        description.into_bytes(),
+                    )]),
+                )
+                .format_bytes(),
+            );
+        }
+    }
+
+    /// Only uses absolute times, so the description doesn't change every tick
+    fn describe(heartbeat: &Heartbeat) -> String {
+        let every = format_duration(heartbeat.every as u64);
+        match heartbeat.last_ping {
+            Some(last_ping) => format!(
+                "missed, expected every {every}, last ping {}",
+                Local
+                    .timestamp_opt(last_ping, 0)
+                    .unwrap()
+                    .format("%d %b %H:%M")
+            ),
+            None => format!("missed, expected every {every}, never pinged"),
+        }
+    }
+}
+
+impl Runnable for HeartbeatService {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let heartbeats = Arc::clone(&self.heartbeats);
+        let path = self.path.clone();
+        let unsaved = Arc::clone(&self.unsaved);
+
+        tokio::spawn(async move {
+            // copied out, pings wait on this lock while the bus is locked
+            let save = || {
+                if let Some(path) = &path {
+                    let heartbeats = heartbeats.lock().unwrap().clone();
+                    HeartbeatService::save(path, &heartbeats);
+                }
+            };
+
+            let mut tick = tokio::time::interval(Duration::from_millis(1000));
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tick.tick() => HeartbeatService::publish_missed(&event_bus, &heartbeats),
+                    _ = unsaved.notified() => save(),
+                }
+            }
+            // a ping that came in while shutting down
+            save();
+        })
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use log::warn;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        event_type::EventType,
+        job::JobProgress,
+        status_event::{StatusEvent, StatusTransition},
+    },
+    traits::runnable::Runnable,
+};
+
+pub const HISTORY_FILE: &str = "history.jsonl";
+const HISTORY_LIMIT: usize = 5000;
+/// How many finished runs per title the baseline is taken from
+const BASELINE_RUNS: usize = 10;
+/// A job is overdue once it's taken this many times its usual duration...
+const OVERDUE_FACTOR: f64 = 1.5;
+/// ...and at least this many seconds longer, so short jobs don't flap
+const OVERDUE_SLACK: i64 = 60;
+
+/// What `HistoryWriter` does to the file, in the order it was asked to
+enum HistoryWrite {
+    Append(StatusEvent),
+    /// Replaces the file with just these, keeping it from growing forever
+    Rewrite(Vec<StatusEvent>),
+}
+
+struct OpenStatus {
+    started: i64,
+    source: String,
+    description: String,
+}
+
+/// Every status transition, kept in memory and appended to a JSON lines file so
+/// the timeline survives restarts. The file is written by `HistoryWriter`, the
+/// history is updated from bus subscribers and those shouldn't wait on the disk
+pub struct StatusHistory {
+    events: VecDeque<StatusEvent>,
+    open: HashMap<String, OpenStatus>,
+    /// Durations of the last finished socket jobs per title, oldest first
+    durations: HashMap<String, VecDeque<i64>>,
+    writes: Option<mpsc::UnboundedSender<HistoryWrite>>,
+    /// Lines added to the file since it was last rewritten
+    appended: usize,
+}
+
+impl StatusHistory {
+    /// Reads the last `HISTORY_LIMIT` events from `path`, rewriting the file if
+    /// it has grown a lot past that. Without a path nothing is written and
+    /// there's no writer to run
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<HistoryWriter>) {
+        let (writes, writer) = match &path {
+            Some(path) => {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                (
+                    Some(sender),
+                    Some(HistoryWriter::new(path.clone(), receiver)),
+                )
+            }
+            None => (None, None),
+        };
+        let mut history = Self {
+            events: VecDeque::new(),
+            open: HashMap::new(),
+            durations: HashMap::new(),
+            writes,
+            appended: 0,
+        };
+
+        let Some(contents) = path.and_then(|path| fs::read_to_string(path).ok()) else {
+            return (history, writer);
+        };
+
+        let mut line_count = 0;
+        for line in contents.lines() {
+            line_count += 1;
+            match serde_json::from_str::<StatusEvent>(line) {
+                Ok(event) => history.remember(event),
+                Err(err) => warn!("skipping bad history line: {err}"),
+            }
+        }
+
+        // processes are picked up again by the watcher, only jobs from the socket
+        // can still be running across a restart
+        history
+            .open
+            .retain(|_, open| open.source == EventType::Socket.name());
+
+        history.appended = line_count;
+        if line_count > HISTORY_LIMIT * 2 {
+            history.compact();
+        }
+
+        (history, writer)
+    }
+
+    /// Records a status message arriving, as started if it's new and updated if
+    /// the description changed
+    pub fn seen(&mut self, msg: &EventBusMessage) {
+        let description = msg.get_field_string(EventFieldType::Description);
+        let transition = match self.open.get(msg.title()) {
+            None => StatusTransition::Started,
+            Some(open) if open.description != description => StatusTransition::Updated,
+            Some(_) => return,
+        };
+
+        self.push(StatusEvent {
+            ts: msg.ts(),
+            title: msg.title().to_string(),
+            source: msg.event_type().name().to_string(),
+            transition,
+            description,
+            duration: None,
+        });
+    }
+
+    /// Records a status going away, e.g. `Done` or `Dismissed`
+    pub fn closed(&mut self, msg: &EventBusMessage, transition: StatusTransition) {
+        let now = chrono::Utc::now().timestamp();
+        let duration = self.open.get(msg.title()).map(|open| now - open.started);
+
+        self.push(StatusEvent {
+            ts: now,
+            title: msg.title().to_string(),
+            source: msg.event_type().name().to_string(),
+            transition,
+            description: msg.get_field_string(EventFieldType::Description),
+            duration,
+        });
+    }
+
+    /// Records something done to a status that keeps it open, like acknowledging it
+    pub fn noted(&mut self, msg: &EventBusMessage, transition: StatusTransition) {
+        self.push(StatusEvent {
+            ts: chrono::Utc::now().timestamp(),
+            title: msg.title().to_string(),
+            source: msg.event_type().name().to_string(),
+            transition,
+            description: msg.get_field_string(EventFieldType::Description),
+            duration: None,
+        });
+    }
+
+    /// Title and description of everything still open, like socket jobs that
+    /// were running when we restarted
+    pub fn open_statuses(&self) -> Vec<(String, String)> {
+        self.open
+            .iter()
+            .map(|(title, open)| (title.clone(), open.description.clone()))
+            .collect()
+    }
+
+    /// When an open status was first seen
+    pub fn since(&self, title: &str) -> Option<i64> {
+        self.open.get(title).map(|open| open.started)
+    }
+
+    /// Elapsed time and the expected duration of an open socket job
+    pub fn job(&self, title: &str) -> Option<JobProgress> {
+        let open = self.open.get(title)?;
+        if open.source != EventType::Socket.name() {
+            return None;
+        }
+
+        let elapsed = chrono::Utc::now().timestamp() - open.started;
+        let expected = self.baseline(title);
+        let overdue = expected.is_some_and(|expected| {
+            elapsed as f64 > expected as f64 * OVERDUE_FACTOR && elapsed - expected > OVERDUE_SLACK
+        });
+
+        Some(JobProgress {
+            elapsed,
+            expected,
+            overdue,
+        })
+    }
+
+    /// Median of the last finished runs, so one slow run doesn't skew it
+    fn baseline(&self, title: &str) -> Option<i64> {
+        let mut durations: Vec<i64> = self.durations.get(title)?.iter().copied().collect();
+        durations.sort_unstable();
+        durations.get(durations.len() / 2).copied()
+    }
+
+    pub fn events(&self) -> &VecDeque<StatusEvent> {
+        &self.events
+    }
+
+    fn push(&mut self, event: StatusEvent) {
+        if let Some(writes) = &self.writes {
+            let _ = writes.send(HistoryWrite::Append(event.clone()));
+            self.appended += 1;
+        }
+
+        self.remember(event);
+        // the file only needs what's kept in memory, rewrite it once it holds
+        // a lot more than that
+        if self.appended > HISTORY_LIMIT * 2 {
+            self.compact();
+        }
+    }
+
+    fn remember(&mut self, event: StatusEvent) {
+        match event.transition {
+            StatusTransition::Started => {
+                self.open.insert(
+                    event.title.clone(),
+                    OpenStatus {
+                        started: event.ts,
+                        source: event.source.clone(),
+                        description: event.description.clone(),
+                    },
+                );
+            }
+            StatusTransition::Updated => {
+                if let Some(open) = self.open.get_mut(&event.title) {
+                    open.description = event.description.clone();
+                }
+            }
+            StatusTransition::Done
+            | StatusTransition::Failed
+            | StatusTransition::Expired
+            | StatusTransition::Dismissed => {
+                self.open.remove(&event.title);
+            }
+            StatusTransition::Acknowledged | StatusTransition::Snoozed => (),
+        }
+
+        // expired statuses never finished, they'd only drag the baseline down
+        if let (StatusTransition::Done, Some(duration)) = (&event.transition, event.duration) {
+            if event.source == EventType::Socket.name() {
+                let durations = self.durations.entry(event.title.clone()).or_default();
+                durations.push_back(duration);
+                if durations.len() > BASELINE_RUNS {
+                    durations.pop_front();
+                }
+            }
+        }
+
+        self.events.push_back(event);
+        if self.events.len() > HISTORY_LIMIT {
+            self.events.pop_front();
+        }
+    }
+
+    fn compact(&mut self) {
+        let Some(writes) = &self.writes else {
+            return;
+        };
+
+        let events = self.events.iter().cloned().collect();
+        let _ = writes.send(HistoryWrite::Rewrite(events));
+        self.appended = self.events.len();
+    }
+}
+
+/// Writes what `StatusHistory` records to its file, off the event bus
+pub struct HistoryWriter {
+    path: Arc<PathBuf>,
+    writes: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<HistoryWrite>>>,
+}
+
+impl HistoryWriter {
+    fn new(path: PathBuf, writes: mpsc::UnboundedReceiver<HistoryWrite>) -> Self {
+        Self {
+            path: Arc::new(path),
+            writes: Arc::new(tokio::sync::Mutex::new(writes)),
+        }
+    }
+
+    fn write(path: &PathBuf, write: HistoryWrite) {
+        let written = match write {
+            HistoryWrite::Append(event) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(&event)?)),
+            HistoryWrite::Rewrite(events) => {
+                let contents = events
+                    .iter()
+                    .filter_map(|event| serde_json::to_string(event).ok())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                fs::write(path, contents + "\n")
+            }
+        };
+        if let Err(err) = written {
+            warn!("unable to write history to {}: {err}", path.display());
+        }
+    }
+}
+
+impl Runnable for HistoryWriter {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let path = Arc::clone(&self.path);
+        let writes = Arc::clone(&self.writes);
+
+        tokio::spawn(async move {
+            let mut writes = writes.lock().await;
+            loop {
+                let write = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    Some(write) = writes.recv() => write,
+                };
+                HistoryWriter::write(&path, write);
+            }
+
+            // transitions from shutting down still make it to the file
+            while let Ok(write) = writes.try_recv() {
+                HistoryWriter::write(&path, write);
+            }
+        })
+    }
+}
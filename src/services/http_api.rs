@@ -0,0 +1,461 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::{info, trace, warn};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    api::websocket::{self, OPCODE_CLOSE, OPCODE_PING, OPCODE_PONG, OPCODE_TEXT},
+    models::{config::HttpConfig, socket_message::SocketQuery},
+    traits::runnable::Runnable,
+};
+
+use super::{
+    event_bus::EventBus,
+    query::{Queries, TopicStream},
+};
+
+/// Requests are GETs without a body, their headers never get close to this
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A parsed request line and headers, header names are lowercase
+struct Request {
+    method: String,
+    path: String,
+    params: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (decode(key), decode(value)))
+            .collect();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+
+        Some(Self {
+            method,
+            path: decode(path),
+            params,
+            headers,
+        })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Read-only REST endpoints with the same answers as the socket queries, and a
+/// WebSocket streaming event bus topics as JSON
+///
+/// - `GET /api/status`
+/// - `GET /api/metrics`
+/// - `GET /api/metrics/<name>?since=5m`
+/// - `GET /api/containers`
+/// - `GET /api/processes`
+/// - `GET /api/events?topic=<topic>`, upgrades to a WebSocket, all topics by default
+pub struct HttpApiService {
+    listener: Arc<TcpListener>,
+    event_bus: Arc<Mutex<EventBus>>,
+    queries: Queries,
+    token: Arc<Option<String>>,
+}
+
+impl HttpApiService {
+    /// Binds right away, so a taken port fails startup instead of the service
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &HttpConfig) -> io::Result<Self> {
+        let address = config.listen.parse::<SocketAddr>().map_err(|_| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid http listen address '{}'", config.listen),
+            )
+        })?;
+        if !address.ip().is_loopback() && config.token.is_none() {
+            warn!("http api on {address} is reachable by others without a token");
+        }
+
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!("http api listening on {address}");
+
+        Ok(Self {
+            listener: Arc::new(TcpListener::from_std(listener)?),
+            queries: Queries::new(Arc::clone(&event_bus)),
+            event_bus,
+            token: Arc::new(config.token.clone()),
+        })
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> io::Result<Request> {
+        let mut buffer = vec![];
+        let mut chunk = [0u8; 1024];
+        while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 || buffer.len() + read > MAX_REQUEST_LEN {
+                return Err(io::Error::new(ErrorKind::InvalidData, "incomplete request"));
+            }
+            buffer.extend(&chunk[..read]);
+        }
+
+        Request::parse(&String::from_utf8_lossy(&buffer)).ok_or(io::Error::new(
+            ErrorKind::InvalidData,
+            "invalid request line",
+        ))
+    }
+
+    async fn handle(
+        mut stream: TcpStream,
+        event_bus: Arc<Mutex<EventBus>>,
+        queries: Queries,
+        token: Arc<Option<String>>,
+        cancel: CancellationToken,
+    ) -> io::Result<()> {
+        let request =
+            tokio::time::timeout(REQUEST_TIMEOUT, HttpApiService::read_request(&mut stream))
+                .await
+                .map_err(|_| io::Error::new(ErrorKind::TimedOut, "request took too long"))??;
+        trace!("http api: {} {}", request.method, request.path);
+
+        if let Some(token) = token.as_deref() {
+            let bearer = request
+                .header("authorization")
+                .and_then(|value| value.strip_prefix("Bearer "));
+            let param = request.params.get("token").map(String::as_str);
+            if !same_token(bearer, token) && !same_token(param, token) {
+                return HttpApiService::respond(
+                    &mut stream,
+                    401,
+                    "Unauthorized",
+                    "missing or wrong token",
+                )
+                .await;
+            }
+        }
+        if request.method != "GET" {
+            return HttpApiService::respond(&mut stream, 405, "Method Not Allowed", "read only")
+                .await;
+        }
+
+        let path = request.path.trim_end_matches('/');
+        let query = match path {
+            "/api/status" => SocketQuery::StatusList,
+            "/api/metrics" => SocketQuery::MetricsCurrent,
+            "/api/containers" => SocketQuery::ContainersList,
+            "/api/processes" => SocketQuery::ProcessesWatched,
+            "/api/events" => {
+                let topic = request.params.get("topic").map_or("*", String::as_str);
+                return HttpApiService::stream_topic(stream, &request, topic, event_bus, cancel)
+                    .await;
+            }
+            _ => match path.strip_prefix("/api/metrics/") {
+                Some(metric) => SocketQuery::MetricsRange {
+                    metric: metric.to_string(),
+                    since: request.params.get("since").cloned(),
+                },
+                None => {
+                    return HttpApiService::respond(&mut stream, 404, "Not Found", "unknown path")
+                        .await
+                }
+            },
+        };
+
+        let reply = queries.ask(&query).await;
+        match serde_json::from_str::<serde_json::Value>(&reply) {
+            Ok(value) if value.get("error").is_some() => {
+                HttpApiService::write_response(&mut stream, 400, "Bad Request", &reply).await
+            }
+            _ => HttpApiService::write_response(&mut stream, 200, "OK", &reply).await,
+        }
+    }
+
+    async fn respond(
+        stream: &mut TcpStream,
+        code: u16,
+        reason: &str,
+        error: &str,
+    ) -> io::Result<()> {
+        let body = serde_json::json!({ "error": error }).to_string();
+        HttpApiService::write_response(stream, code, reason, &body).await
+    }
+
+    async fn write_response(
+        stream: &mut TcpStream,
+        code: u16,
+        reason: &str,
+        body: &str,
+    ) -> io::Result<()> {
+        let mut response = format!(
+            "HTTP/1.1 {code} {reason}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n",
+            body.len()
+        );
+        if code == 401 {
+            response.push_str("WWW-Authenticate: Bearer\r\n");
+        }
+        response.push_str("\r\n");
+        response.push_str(body);
+        stream.write_all(response.as_bytes()).await
+    }
+
+    /// Upgrades to a WebSocket and sends every message on `topic` as a text frame
+    /// until either side closes it
+    async fn stream_topic(
+        mut stream: TcpStream,
+        request: &Request,
+        topic: &str,
+        event_bus: Arc<Mutex<EventBus>>,
+        cancel: CancellationToken,
+    ) -> io::Result<()> {
+        let upgrade = request
+            .header("upgrade")
+            .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+        let Some(key) = request.header("sec-websocket-key").filter(|_| upgrade) else {
+            return HttpApiService::respond(&mut stream, 426, "Upgrade Required", "websocket only")
+                .await;
+        };
+        // browsers send one, so pages from other sites can't read the stream
+        // through them. Other clients don't, and get the token check only
+        if let Some(origin) = request.header("origin") {
+            let origin_host = origin.split_once("://").map(|(_, host)| host);
+            if origin_host.is_none() || origin_host != request.header("host") {
+                return HttpApiService::respond(&mut stream, 403, "Forbidden", "foreign origin")
+                    .await;
+            }
+        }
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                     Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    websocket::accept_key(key)
+                )
+                .as_bytes(),
+            )
+            .await?;
+
+        let mut messages = TopicStream::subscribe(event_bus, topic);
+        let (mut reader, mut writer) = stream.into_split();
+        // read on the side, a frame cut off by an outgoing message would be lost
+        let (sender, mut frames) = mpsc::channel(1);
+        let reading = tokio::spawn(async move {
+            loop {
+                let frame = websocket::read_frame(&mut reader).await;
+                let failed = frame.is_err();
+                if sender.send(frame).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let result = async {
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        return writer.write_all(&websocket::frame(OPCODE_CLOSE, &[])).await;
+                    }
+                    Some(frame) = frames.recv() => {
+                        let frame = frame?;
+                        match frame.opcode {
+                            OPCODE_CLOSE => {
+                                let close = websocket::frame(OPCODE_CLOSE, &frame.payload);
+                                return writer.write_all(&close).await;
+                            }
+                            OPCODE_PING => {
+                                let pong = websocket::frame(OPCODE_PONG, &frame.payload);
+                                writer.write_all(&pong).await?;
+                            }
+                            // nothing to say to us, it's read only
+                            _ => (),
+                        }
+                    }
+                    msg = messages.next() => {
+                        // the client fell behind and was unsubscribed
+                        let Some(msg) = msg else {
+                            return writer.write_all(&websocket::frame(OPCODE_CLOSE, &[])).await;
+                        };
+                        let text = websocket::frame(OPCODE_TEXT, msg.to_string().as_bytes());
+                        writer.write_all(&text).await?;
+                    }
+                }
+            }
+        }
+        .await;
+        reading.abort();
+        result
+    }
+}
+
+impl Runnable for HttpApiService {
+    fn name(&self) -> &'static str {
+        "http_api"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let listener = Arc::clone(&self.listener);
+        let event_bus = Arc::clone(&self.event_bus);
+        let queries = self.queries.clone();
+        let token = Arc::clone(&self.token);
+
+        tokio::spawn(async move {
+            loop {
+                let stream = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            warn!("http api: {err}");
+                            continue;
+                        }
+                    },
+                };
+
+                let event_bus = Arc::clone(&event_bus);
+                let queries = queries.clone();
+                let token = Arc::clone(&token);
+                let cancel = cancel.clone();
+                tokio::spawn(async move {
+                    if let Err(err) =
+                        HttpApiService::handle(stream, event_bus, queries, token, cancel).await
+                    {
+                        trace!("http api: {err}");
+                    }
+                });
+            }
+        })
+    }
+}
+
+/// Percent-decoding for paths and query parameters
+fn decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Takes as long for every wrong token of the right length, so guesses can't
+/// be narrowed down by timing
+fn same_token(given: Option<&str>, token: &str) -> bool {
+    given.is_some_and(|given| {
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
+            event_type::EventType,
+        },
+        services::{process_watcher, render},
+    };
+
+    use super::*;
+
+    /// Statuses expiring publish a render mark, which used to take down every
+    /// stream of all topics
+    #[tokio::test]
+    async fn events_without_topic_survive_status_expiry() {
+        let event_bus = Arc::new(Mutex::new(EventBus::new()));
+        let config = HttpConfig {
+            enabled: true,
+            listen: "127.0.0.1:0".to_string(),
+            token: None,
+        };
+        let service = HttpApiService::new(Arc::clone(&event_bus), &config).unwrap();
+        let address = service.listener.local_addr().unwrap();
+        let cancel = CancellationToken::new();
+        service.start(cancel.clone());
+
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                b"GET /api/events HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                  Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let mut response = vec![];
+        while !response.ends_with(b"\r\n\r\n") {
+            response.push(stream.read_u8().await.unwrap());
+        }
+        assert!(response.starts_with(b"HTTP/1.1 101"));
+        // the stream subscribes right after answering
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        {
+            let lock = event_bus.lock().unwrap();
+            render::mark_dirty(&lock);
+            lock.publish(
+                process_watcher::EVENT_TOPIC,
+                EventBusMessage::new(
+                    "sshd",
+                    EventType::Process,
+                    Some(vec![(EventFieldType::Description, b"Running".to_vec())]),
+                )
+                .format_bytes(),
+            );
+        }
+
+        let mut titles = vec![];
+        for _ in 0..2 {
+            let frame =
+                tokio::time::timeout(Duration::from_secs(1), websocket::read_frame(&mut stream))
+                    .await
+                    .expect("stream went quiet")
+                    .unwrap();
+            assert_eq!(frame.opcode, OPCODE_TEXT);
+            let msg: serde_json::Value = serde_json::from_slice(&frame.payload).unwrap();
+            titles.push(msg["title"].as_str().unwrap().to_string());
+        }
+        assert_eq!(titles, [render::DIRTY_TOPIC, "sshd"]);
+        cancel.cancel();
+    }
+}
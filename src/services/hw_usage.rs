@@ -1,10 +1,11 @@
 use std::{
     sync::{Arc, Mutex},
-    thread::sleep,
-    time::Duration,
+    time::Instant,
 };
 
-use sysinfo::System;
+use sysinfo::{MemoryRefreshKind, Networks, Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     models::{
@@ -12,61 +13,155 @@ use crate::{
         event_type::EventType,
     },
     traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
 };
 
-use super::event_bus::EventBus;
+use super::{event_bus::EventBus, sampling::Sampling};
 
 pub const EVENT_TOPIC: &str = "hw_usage";
+/// Our own CPU use in percent of a core and memory in bytes
+pub const SELF_TOPIC: &str = "self_usage";
+
+/// Interfaces and when they were last refreshed, their counters are deltas
+struct NetworkCounters {
+    networks: Networks,
+    refreshed: Instant,
+}
 
 pub struct HwUsageService {
     event_bus: Arc<Mutex<EventBus>>,
     system: Arc<Mutex<System>>,
+    networks: Arc<Mutex<NetworkCounters>>,
+    sampling: Sampling,
+    pid: Option<Pid>,
 }
 
 impl HwUsageService {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, sampling: Sampling) -> Self {
         Self {
             event_bus,
-            system: Arc::new(Mutex::new(System::new_all())),
+            // starts out empty, only what we sample gets refreshed
+            system: Arc::new(Mutex::new(System::new())),
+            networks: Arc::new(Mutex::new(NetworkCounters {
+                networks: Networks::new_with_refreshed_list(),
+                refreshed: Instant::now(),
+            })),
+            sampling,
+            pid: sysinfo::get_current_pid().ok(),
         }
     }
 
-    fn poll_system(event_bus: Arc<Mutex<EventBus>>, system: Arc<Mutex<System>>) {
+    /// Bytes per second received and sent since the last call, loopback aside
+    fn network_rates(counters: &mut NetworkCounters) -> (f64, f64) {
+        counters.networks.refresh(true);
+        let elapsed = counters.refreshed.elapsed().as_secs_f64().max(0.001);
+        counters.refreshed = Instant::now();
+
+        let (rx, tx) = counters
+            .networks
+            .iter()
+            .filter(|(name, _)| *name != "lo")
+            .fold((0, 0), |(rx, tx), (_, data)| {
+                (rx + data.received(), tx + data.transmitted())
+            });
+        (rx as f64 / elapsed, tx as f64 / elapsed)
+    }
+
+    fn poll_system(
+        event_bus: &Mutex<EventBus>,
+        system: &Mutex<System>,
+        networks: &Mutex<NetworkCounters>,
+        pid: Option<Pid>,
+    ) {
         let mut system = system.lock().unwrap();
-        (*system).refresh_cpu_usage();
-        (*system).refresh_memory();
+        system.refresh_cpu_usage();
+        system.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
 
         let cpu_usage = system.global_cpu_usage() as f64;
         let ram_usage = (system.used_memory() as f64 / system.total_memory() as f64) * 100.0;
+        let load = System::load_average().one;
+        let (rx, tx) = HwUsageService::network_rates(&mut networks.lock().unwrap());
 
-        let cpu_bytes = cpu_usage.to_bits().to_le_bytes().to_vec();
-        let ram_bytes = ram_usage.to_bits().to_le_bytes().to_vec();
-
-        event_bus.lock().unwrap().publish(
+        let mut messages = vec![(
             EVENT_TOPIC,
             EventBusMessage::new(
                 "usage",
                 EventType::HWusage,
                 Some(vec![
-                    (EventFieldType::Cpu, cpu_bytes),
-                    (EventFieldType::Memory, ram_bytes),
+                    (
+                        EventFieldType::Cpu,
+                        cpu_usage.to_bits().to_le_bytes().to_vec(),
+                    ),
+                    (
+                        EventFieldType::Memory,
+                        ram_usage.to_bits().to_le_bytes().to_vec(),
+                    ),
+                    (EventFieldType::Load, load.to_bits().to_le_bytes().to_vec()),
+                    (
+                        EventFieldType::NetworkRx,
+                        rx.to_bits().to_le_bytes().to_vec(),
+                    ),
+                    (
+                        EventFieldType::NetworkTx,
+                        tx.to_bits().to_le_bytes().to_vec(),
+                    ),
                 ]),
-            )
-            .format_bytes(),
-        );
+            ),
+        )];
+
+        if let Some(pid) = pid {
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                false,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+            if let Some(process) = system.process(pid) {
+                let cpu = process.cpu_usage() as f64;
+                let memory = process.memory() as f64;
+                messages.push((
+                    SELF_TOPIC,
+                    EventBusMessage::new(
+                        "server-tui",
+                        EventType::HWusage,
+                        Some(vec![
+                            (EventFieldType::Cpu, cpu.to_bits().to_le_bytes().to_vec()),
+                            (
+                                EventFieldType::Memory,
+                                memory.to_bits().to_le_bytes().to_vec(),
+                            ),
+                        ]),
+                    ),
+                ));
+            }
+        }
+        drop(system);
+
+        let lock = event_bus.lock().unwrap();
+        for (topic, msg) in messages {
+            lock.publish(topic, msg.format_bytes());
+        }
     }
 }
 
 impl Runnable for HwUsageService {
-    fn run(&self) {
+    fn name(&self) -> &'static str {
+        "hw_usage"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
         let event_bus = Arc::clone(&self.event_bus);
         let system = Arc::clone(&self.system);
+        let networks = Arc::clone(&self.networks);
+        let sampling = self.sampling.clone();
+        let pid = self.pid;
 
         tokio::spawn(async move {
             loop {
-                HwUsageService::poll_system(Arc::clone(&event_bus), Arc::clone(&system));
-                sleep(Duration::from_millis(100));
+                HwUsageService::poll_system(&event_bus, &system, &networks, pid);
+                if cancellable_sleep(&cancel, sampling.interval()).await {
+                    break;
+                }
             }
-        });
+        })
     }
 }
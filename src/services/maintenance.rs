@@ -0,0 +1,195 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{Local, TimeDelta};
+use log::{info, trace};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        config::MaintenanceConfig,
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        event_type::EventType,
+        maintenance::{MaintenanceMode, MaintenanceWindow},
+    },
+    traits::runnable::Runnable,
+    utils::{
+        bytes_helper::bytes_to_i64, cron::CronSchedule, duration::parse_duration,
+        task::cancellable_sleep,
+    },
+};
+
+use super::event_bus::EventBus;
+
+pub const EVENT_TOPIC: &str = "maintenance";
+/// Ad-hoc windows from the socket, titled `start` or `end`
+pub const REQUEST_TOPIC: &str = "maintenance_request";
+pub const REQUEST_START: &str = "start";
+pub const REQUEST_END: &str = "end";
+
+struct Schedule {
+    cron: CronSchedule,
+    duration: i64,
+    reason: String,
+    mode: MaintenanceMode,
+}
+
+#[derive(Default)]
+struct AdHoc {
+    window: Option<MaintenanceWindow>,
+    // windows closing at or before this were ended early and stay closed
+    ended_until: i64,
+}
+
+pub struct MaintenanceService {
+    event_bus: Arc<Mutex<EventBus>>,
+    schedules: Arc<Vec<Schedule>>,
+    ad_hoc: Arc<Mutex<AdHoc>>,
+}
+
+impl MaintenanceService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &[MaintenanceConfig]) -> io::Result<Self> {
+        let schedules = config
+            .iter()
+            .map(|window| {
+                let invalid = |err: String| io::Error::new(ErrorKind::InvalidData, err);
+                Ok(Schedule {
+                    cron: CronSchedule::parse(&window.cron).map_err(invalid)?,
+                    duration: parse_duration(&window.duration)
+                        .and_then(|secs| i64::try_from(secs).ok())
+                        .ok_or(invalid(format!(
+                            "invalid maintenance duration '{}'",
+                            window.duration
+                        )))?,
+                    reason: window.reason.clone(),
+                    mode: window.mode.clone(),
+                })
+            })
+            .collect::<io::Result<Vec<Schedule>>>()?;
+
+        let ad_hoc = Arc::new(Mutex::new(AdHoc::default()));
+        MaintenanceService::subscribe(Arc::clone(&event_bus), Arc::clone(&ad_hoc));
+
+        Ok(Self {
+            event_bus,
+            schedules: Arc::new(schedules),
+            ad_hoc,
+        })
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, ad_hoc: Arc<Mutex<AdHoc>>) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(REQUEST_TOPIC, move |data| {
+            MaintenanceService::on_request(data, Arc::clone(&ad_hoc));
+        });
+    }
+
+    fn on_request(data: Vec<u8>, ad_hoc: Arc<Mutex<AdHoc>>) {
+        let msg = EventBusMessage::from_bytes(data);
+        trace!("MaintenanceService: on_request: {:?}", msg);
+
+        let mut lock = ad_hoc.lock().unwrap();
+        if msg.title() == REQUEST_END {
+            info!("maintenance ended early");
+            lock.window = None;
+            lock.ended_until = chrono::Utc::now().timestamp();
+            return;
+        }
+
+        lock.window = Some(MaintenanceWindow {
+            until: bytes_to_i64(msg.get_field(EventFieldType::Timestamp)),
+            reason: msg.get_field_string(EventFieldType::Description),
+            mode: MaintenanceMode::from_string(&msg.get_field_string(EventFieldType::State)),
+        });
+    }
+
+    /// The ad-hoc window wins over any scheduled one
+    fn current_window(schedules: &[Schedule], ad_hoc: &Mutex<AdHoc>) -> Option<MaintenanceWindow> {
+        let now = Local::now();
+        let mut lock = ad_hoc.lock().unwrap();
+
+        if lock
+            .window
+            .as_ref()
+            .is_some_and(|window| window.until <= now.timestamp())
+        {
+            lock.window = None;
+        }
+        if lock.window.is_some() {
+            return lock.window.clone();
+        }
+
+        schedules
+            .iter()
+            .filter_map(|schedule| {
+                let start = schedule
+                    .cron
+                    .last_start(now, TimeDelta::seconds(schedule.duration))?;
+                let until = start.timestamp() + schedule.duration;
+
+                (until > now.timestamp() && until > lock.ended_until).then(|| MaintenanceWindow {
+                    until,
+                    reason: schedule.reason.clone(),
+                    mode: schedule.mode.clone(),
+                })
+            })
+            .max_by_key(|window| window.until)
+    }
+
+    fn publish(event_bus: &Mutex<EventBus>, window: &Option<MaintenanceWindow>) {
+        let fields = window.as_ref().map(|window| {
+            vec![
+                (
+                    EventFieldType::Timestamp,
+                    window.until.to_le_bytes().to_vec(),
+                ),
+                (
+                    EventFieldType::Description,
+                    window.reason.as_bytes().to_vec(),
+                ),
+                (
+                    EventFieldType::State,
+                    window.mode.to_string().as_bytes().to_vec(),
+                ),
+            ]
+        });
+
+        event_bus.lock().unwrap().publish(
+            EVENT_TOPIC,
+            EventBusMessage::new("maintenance", EventType::Maintenance, fields).format_bytes(),
+        );
+    }
+}
+
+impl Runnable for MaintenanceService {
+    fn name(&self) -> &'static str {
+        "maintenance"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let schedules = Arc::clone(&self.schedules);
+        let ad_hoc = Arc::clone(&self.ad_hoc);
+
+        tokio::spawn(async move {
+            let mut was_active = false;
+            loop {
+                let window = MaintenanceService::current_window(&schedules, &ad_hoc);
+                if window.is_some() != was_active {
+                    was_active = window.is_some();
+                    info!("maintenance window active: {was_active}");
+                }
+
+                MaintenanceService::publish(&event_bus, &window);
+                if cancellable_sleep(&cancel, Duration::from_millis(1000)).await {
+                    break;
+                }
+            }
+        })
+    }
+}
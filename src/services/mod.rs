@@ -1,5 +1,19 @@
+pub mod certs;
 pub mod datetime;
 pub mod event_bus;
+pub mod file_tail;
+pub mod health_check;
+pub mod heartbeat;
+pub mod history;
+pub mod http_api;
 pub mod hw_usage;
+pub mod maintenance;
+pub mod plugins;
 pub mod process_watcher;
+pub mod query;
+pub mod recording;
+pub mod render;
+pub mod sampling;
+pub mod scripts;
 pub mod socket;
+pub mod supervisor;
@@ -0,0 +1,304 @@
+use std::{
+    io::{self, ErrorKind},
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::{info, trace, warn};
+use serde::Deserialize;
+use tokio::{io::AsyncReadExt, process::Command, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        config::PluginConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage, event_type::EventType, plugin_state::PluginState,
+        time_series::Unit,
+    },
+    traits::runnable::Runnable,
+    utils::{duration::parse_duration, task::cancellable_sleep},
+};
+
+use super::event_bus::EventBus;
+
+/// Plugins print a handful of lines, anything past this is a runaway
+const MAX_OUTPUT: u64 = 64 * 1024;
+
+/// What a plugin prints, one per line
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum PluginLine {
+    Metric {
+        metric: String,
+        value: f64,
+        #[serde(default)]
+        unit: Option<Unit>,
+    },
+    Status {
+        status: String,
+        state: PluginState,
+        #[serde(default)]
+        description: String,
+    },
+}
+
+/// Kills a run's process group when dropped, `sh -c` forks for most commands
+/// and killing just the shell would leave the plugin running
+struct ProcessGroup(Option<i32>);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(pgid) = self.0 {
+            // SAFETY: kill has no memory safety requirements, the group is our child's
+            unsafe { libc::kill(-pgid, libc::SIGKILL) };
+        }
+    }
+}
+
+struct Plugin {
+    name: String,
+    command: String,
+    interval: Duration,
+    timeout: Duration,
+}
+
+/// Every plugin publishes on its own topic, metrics with a `Value` and statuses
+/// with a `State`. Whether the plugin itself ran fine is a status titled with
+/// `plugin_title`
+pub fn topic(name: &str) -> String {
+    format!("plugin.{name}")
+}
+
+/// Title of the status raised when a plugin fails, times out or prints garbage
+pub fn plugin_title(name: &str) -> String {
+    format!("plugin {name}")
+}
+
+/// Runs each configured plugin on its own interval, in its own task, so a plugin
+/// that hangs or crashes only takes its own metrics down
+pub struct PluginService {
+    event_bus: Arc<Mutex<EventBus>>,
+    plugins: Arc<Vec<Plugin>>,
+}
+
+impl PluginService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &[PluginConfig]) -> io::Result<Self> {
+        let plugins = config
+            .iter()
+            .map(PluginService::parse_plugin)
+            .collect::<Result<Vec<Plugin>, String>>()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+        Ok(Self {
+            event_bus,
+            plugins: Arc::new(plugins),
+        })
+    }
+
+    fn parse_plugin(config: &PluginConfig) -> Result<Plugin, String> {
+        let duration = |value: &str| {
+            parse_duration(value)
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .ok_or(format!(
+                    "invalid duration '{value}' for plugin '{}'",
+                    config.name
+                ))
+        };
+        // the topic and status titles are built from it
+        if config.name.is_empty() || config.name.contains('|') {
+            return Err(format!("invalid plugin name '{}'", config.name));
+        }
+
+        Ok(Plugin {
+            name: config.name.clone(),
+            command: config.command.clone(),
+            interval: duration(&config.interval)?,
+            timeout: duration(&config.timeout)?,
+        })
+    }
+
+    /// Returns the plugin's output, or why the run failed
+    async fn execute(command: &str) -> Result<String, String> {
+        let mut child = Command::new("sh")
+            .args(["-c", command])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| err.to_string())?;
+        // a timed out run drops this, don't leave anything it started running
+        let mut group = ProcessGroup(child.id().map(|pid| pid as i32));
+
+        let mut output = vec![];
+        if let Some(stdout) = child.stdout.take() {
+            stdout
+                .take(MAX_OUTPUT + 1)
+                .read_to_end(&mut output)
+                .await
+                .map_err(|err| err.to_string())?;
+        }
+        if output.len() as u64 > MAX_OUTPUT {
+            return Err(format!("more than {MAX_OUTPUT} bytes of output"));
+        }
+
+        let status = child.wait().await.map_err(|err| err.to_string())?;
+        // the group may be gone and its ID reused, whatever is left in it is
+        // the plugin's own business
+        group.0 = None;
+        match status.code() {
+            Some(0) => Ok(String::from_utf8_lossy(&output).to_string()),
+            Some(code) => Err(format!("exit {code}")),
+            None => Err("killed by a signal".to_string()),
+        }
+    }
+
+    async fn run_plugin(plugin: &Plugin, event_bus: &Mutex<EventBus>) {
+        let result =
+            match tokio::time::timeout(plugin.timeout, Self::execute(&plugin.command)).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("timed out after {}s", plugin.timeout.as_secs())),
+            };
+
+        let mut messages = vec![];
+        // lines that parse are still published, the first bad one fails the run
+        let result = result.and_then(|output| {
+            let mut invalid = None;
+            for (number, line) in output.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<PluginLine>(line) {
+                    Ok(line) => messages.push(PluginService::message(line)),
+                    Err(_) if invalid.is_some() => (),
+                    Err(err) => invalid = Some(format!("line {}: {err}", number + 1)),
+                }
+            }
+            invalid.map_or(Ok(()), Err)
+        });
+        trace!(
+            "plugin '{}': {} messages, {:?}",
+            plugin.name,
+            messages.len(),
+            result
+        );
+        if let Err(err) = &result {
+            warn!("plugin '{}' failed: {err}", plugin.name);
+        }
+
+        let (state, description) = match result {
+            Ok(()) => (PluginState::Ok, "ran fine".to_string()),
+            Err(err) => (PluginState::Critical, err),
+        };
+        messages.push(status_message(
+            EventType::Plugin,
+            &plugin_title(&plugin.name),
+            &state,
+            &description,
+        ));
+
+        let topic = topic(&plugin.name);
+        let lock = event_bus.lock().unwrap();
+        for msg in messages {
+            lock.publish(&topic, msg.format_bytes());
+        }
+    }
+
+    fn message(line: PluginLine) -> EventBusMessage {
+        match line {
+            PluginLine::Metric {
+                metric,
+                value,
+                unit,
+            } => metric_message(
+                EventType::Plugin,
+                &metric,
+                value,
+                &unit.unwrap_or(Unit::Plain),
+            ),
+            PluginLine::Status {
+                status,
+                state,
+                description,
+            } => status_message(EventType::Plugin, &status, &state, &description),
+        }
+    }
+}
+
+/// A value for the plugins panel, shared with scripts
+pub fn metric_message(
+    event_type: EventType,
+    name: &str,
+    value: f64,
+    unit: &Unit,
+) -> EventBusMessage {
+    EventBusMessage::new(
+        // titles can't have the separator in them
+        &name.replace('|', "/"),
+        event_type,
+        Some(vec![
+            (EventFieldType::Value, value.to_le_bytes().to_vec()),
+            (EventFieldType::Unit, unit.to_string().as_bytes().to_vec()),
+        ]),
+    )
+}
+
+/// A status for the status panel, shared with scripts
+pub fn status_message(
+    event_type: EventType,
+    title: &str,
+    state: &PluginState,
+    description: &str,
+) -> EventBusMessage {
+    EventBusMessage::new(
+        &title.replace('|', "/"),
+        event_type,
+        Some(vec![
+            (EventFieldType::State, state.to_string().as_bytes().to_vec()),
+            (EventFieldType::Description, description.as_bytes().to_vec()),
+        ]),
+    )
+}
+
+impl Runnable for PluginService {
+    fn name(&self) -> &'static str {
+        "plugins"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let plugins = Arc::clone(&self.plugins);
+        info!("starting {} plugins", plugins.len());
+
+        tokio::spawn(async move {
+            let mut tasks = vec![];
+            for i in 0..plugins.len() {
+                let event_bus = Arc::clone(&event_bus);
+                let plugins = Arc::clone(&plugins);
+                let cancel = cancel.clone();
+
+                tasks.push(tokio::spawn(async move {
+                    let plugin = &plugins[i];
+                    loop {
+                        tokio::select! {
+                            _ = cancel.cancelled() => break,
+                            _ = PluginService::run_plugin(plugin, &event_bus) => (),
+                        }
+                        if cancellable_sleep(&cancel, plugin.interval).await {
+                            break;
+                        }
+                    }
+                }));
+            }
+
+            for task in tasks {
+                let _ = task.await;
+            }
+            // like the health checks, don't get restarted over and over
+            cancel.cancelled().await;
+        })
+    }
+}
@@ -1,6 +1,5 @@
 use std::{
     sync::{Arc, Mutex},
-    thread::sleep,
     time::Duration,
 };
 
@@ -10,11 +9,14 @@ use crate::{
         event_type::EventType,
     },
     traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
 };
 use log::trace;
-use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
+use sysinfo::{ProcessRefreshKind, System};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
-use super::event_bus::EventBus;
+use super::{event_bus::EventBus, sampling::Sampling};
 
 pub const EVENT_TOPIC: &str = "process_watcher";
 
@@ -22,16 +24,20 @@ pub struct ProcessWatcher {
     system: Arc<Mutex<System>>,
     to_watch: Arc<Mutex<Vec<String>>>,
     event_bus: Arc<Mutex<EventBus>>,
+    sampling: Sampling,
 }
 
 impl ProcessWatcher {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>, processes_to_watch: Vec<String>) -> Self {
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        processes_to_watch: Vec<String>,
+        sampling: Sampling,
+    ) -> Self {
         Self {
-            system: Arc::new(Mutex::new(System::new_with_specifics(
-                RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
-            ))),
+            system: Arc::new(Mutex::new(System::new())),
             event_bus,
             to_watch: Arc::new(Mutex::new(processes_to_watch)),
+            sampling,
         }
     }
 
@@ -39,12 +45,14 @@ impl ProcessWatcher {
         event_bus: Arc<Mutex<EventBus>>,
         system: Arc<Mutex<System>>,
         to_watch: Arc<Mutex<Vec<String>>>,
+        interval: Duration,
     ) {
+        // names are all we match on, and they come with every refresh
         let mut lock = system.lock().unwrap();
         (*lock).refresh_processes_specifics(
             sysinfo::ProcessesToUpdate::All,
             true,
-            ProcessRefreshKind::nothing().with_cmd(UpdateKind::Always),
+            ProcessRefreshKind::nothing(),
         );
         let mut count_found = 0;
         for process in (*lock).processes().values() {
@@ -59,15 +67,22 @@ impl ProcessWatcher {
                 .position(|elem| name.contains(&elem.to_lowercase()));
 
             let bytes = "Running".as_bytes().to_vec();
-            if pos.is_some() {
+            // the status panel keeps the entry until it's overdue by this
+            let interval = i64::try_from(interval.as_secs())
+                .unwrap_or(i64::MAX)
+                .to_le_bytes()
+                .to_vec();
+            if let Some(pos) = pos {
                 trace!("before send: {:?}", bytes);
-                let pos = pos.unwrap();
                 event_bus.lock().unwrap().publish(
                     EVENT_TOPIC,
                     EventBusMessage::new(
                         watch_lock.get(pos).unwrap(),
                         EventType::Process,
-                        Some(vec![(EventFieldType::Description, bytes)]),
+                        Some(vec![
+                            (EventFieldType::Description, bytes),
+                            (EventFieldType::Interval, interval),
+                        ]),
                     )
                     .format_bytes(),
                 );
@@ -78,20 +93,30 @@ impl ProcessWatcher {
 }
 
 impl Runnable for ProcessWatcher {
-    fn run(&self) {
+    fn name(&self) -> &'static str {
+        "process_watcher"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
         let event_bus = Arc::clone(&self.event_bus);
         let system = Arc::clone(&self.system);
         let to_watch = Arc::clone(&self.to_watch);
+        let sampling = self.sampling.clone();
 
         tokio::spawn(async move {
             loop {
+                // read once, going idle mid-sleep mustn't outlast what was published
+                let interval = sampling.interval();
                 ProcessWatcher::watch_processes(
                     Arc::clone(&event_bus),
                     Arc::clone(&system),
                     Arc::clone(&to_watch),
+                    interval,
                 );
-                sleep(Duration::from_millis(1000));
+                if cancellable_sleep(&cancel, interval).await {
+                    break;
+                }
             }
-        });
+        })
     }
 }
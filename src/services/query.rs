@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use log::warn;
+use serde_json::Value;
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot,
+};
+
+use crate::models::{
+    event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
+    event_type::EventType, socket_message::SocketQuery,
+};
+
+use super::event_bus::EventBus;
+
+/// Read requests for the app, titled with an ID for the reply. The description
+/// is the `SocketQuery` as JSON
+pub const QUERY_TOPIC: &str = "query";
+/// Answers to `QUERY_TOPIC`, titled with the request's ID and the JSON to send
+/// back as the description
+pub const QUERY_REPLY_TOPIC: &str = "query_reply";
+/// Clients get an error instead once the app took this long
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How far a stream's reader may fall behind its topic
+const STREAM_BUFFER: usize = 1024;
+
+/// Shared by every `Queries`, so their replies can't be mixed up
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Queries waiting for a reply, by ID
+type Pending = Arc<Mutex<HashMap<String, oneshot::Sender<String>>>>;
+
+/// Asks the app, which has the data the panels show, for the answer to a
+/// `SocketQuery`. Used by the socket and the HTTP API
+#[derive(Clone)]
+pub struct Queries {
+    event_bus: Arc<Mutex<EventBus>>,
+    pending: Pending,
+}
+
+impl Queries {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let replies = Arc::clone(&pending);
+        event_bus
+            .lock()
+            .unwrap()
+            .subscribe(QUERY_REPLY_TOPIC, move |data| {
+                let Some(msg) = EventBusMessage::parse(&data) else {
+                    return;
+                };
+                // the client may have given up already, or it's for another `Queries`
+                if let Some(reply) = replies.lock().unwrap().remove(msg.title()) {
+                    let _ = reply.send(msg.get_field_string(EventFieldType::Description));
+                }
+            });
+
+        Self { event_bus, pending }
+    }
+
+    /// The answer as JSON, or an `error` when the app doesn't answer in time
+    pub async fn ask(&self, query: &SocketQuery) -> String {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed).to_string();
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), sender);
+
+        let query = serde_json::to_string(query).unwrap();
+        self.event_bus.lock().unwrap().publish(
+            QUERY_TOPIC,
+            EventBusMessage::new(
+                &id,
+                EventType::Socket,
+                Some(vec![(EventFieldType::Description, query.into_bytes())]),
+            )
+            .format_bytes(),
+        );
+
+        match tokio::time::timeout(QUERY_TIMEOUT, receiver).await {
+            Ok(Ok(reply)) => reply,
+            _ => {
+                self.pending.lock().unwrap().remove(&id);
+                serde_json::json!({ "error": "no answer from the app" }).to_string()
+            }
+        }
+    }
+}
+
+/// Messages published on a topic as JSON, see `EventBusMessage::to_json`.
+/// Unsubscribes when dropped
+pub struct TopicStream {
+    event_bus: Arc<Mutex<EventBus>>,
+    topic: String,
+    id: i32,
+    receiver: mpsc::Receiver<Vec<u8>>,
+    overflowed: Arc<AtomicBool>,
+}
+
+impl TopicStream {
+    /// `*` gets every topic
+    pub fn subscribe(event_bus: Arc<Mutex<EventBus>>, topic: &str) -> Self {
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let overflowed = Arc::new(AtomicBool::new(false));
+        let full = Arc::clone(&overflowed);
+        let id = event_bus.lock().unwrap().subscribe(topic, move |data| {
+            if let Err(TrySendError::Full(_)) = sender.try_send(data) {
+                full.store(true, Ordering::Relaxed);
+            }
+        });
+        Self {
+            event_bus,
+            topic: topic.to_string(),
+            id,
+            receiver,
+            overflowed,
+        }
+    }
+
+    /// Payloads that aren't messages are skipped, `*` sees every topic and some
+    /// only publish to mark something. Ends once the reader fell `STREAM_BUFFER`
+    /// messages behind, rather than quietly missing some
+    pub async fn next(&mut self) -> Option<Value> {
+        loop {
+            if self.overflowed.load(Ordering::Relaxed) {
+                warn!("{} stream fell behind, closing it", self.topic);
+                return None;
+            }
+            let data = self.receiver.recv().await?;
+            if let Some(msg) = EventBusMessage::parse(&data) {
+                let mut msg = msg.to_json();
+                msg["topic"] = Value::from(self.topic.as_str());
+                return Some(msg);
+            }
+        }
+    }
+}
+
+impl Drop for TopicStream {
+    fn drop(&mut self) {
+        self.event_bus
+            .lock()
+            .unwrap()
+            .unsubscribe(&self.topic, self.id);
+    }
+}
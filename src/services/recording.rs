@@ -0,0 +1,197 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{event_bus_message::EventBusMessage, recorded_event::RecordedEvent},
+    traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
+};
+
+use super::{
+    event_bus::EventBus,
+    query::{QUERY_REPLY_TOPIC, QUERY_TOPIC},
+    socket::SNAPSHOT_REQUEST_TOPIC,
+};
+
+/// Requests from clients, answering them again would only write snapshots and
+/// replies nobody asked for
+const NOT_REPLAYED: [&str; 3] = [QUERY_TOPIC, QUERY_REPLY_TOPIC, SNAPSHOT_REQUEST_TOPIC];
+
+/// Writes every publish on the event bus to a file, a `RecordedEvent` per line
+pub struct Recorder {
+    file: Arc<Mutex<BufWriter<File>>>,
+    events: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<RecordedEvent>>>,
+}
+
+impl Recorder {
+    /// Taps the bus right away, so nothing published before the services start
+    /// is missed
+    pub fn new(event_bus: &Mutex<EventBus>, path: &Path) -> io::Result<Self> {
+        let file = File::create(path)?;
+        info!("recording the event bus to {}", path.display());
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        // no writing while the bus is locked, the task does that
+        event_bus.lock().unwrap().tap(move |topic, payload| {
+            let _ = sender.send(RecordedEvent {
+                ts: chrono::Utc::now().timestamp_millis(),
+                topic: topic.to_string(),
+                payload: String::from_utf8_lossy(payload).to_string(),
+            });
+        });
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(BufWriter::new(file))),
+            events: Arc::new(tokio::sync::Mutex::new(receiver)),
+        })
+    }
+
+    fn write(file: &mut BufWriter<File>, event: &RecordedEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut *file, event)?;
+        file.write_all(b"\n")
+    }
+}
+
+impl Runnable for Recorder {
+    fn name(&self) -> &'static str {
+        "recorder"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let file = Arc::clone(&self.file);
+        let events = Arc::clone(&self.events);
+
+        tokio::spawn(async move {
+            let mut events = events.lock().await;
+            loop {
+                let event = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    Some(event) = events.recv() => event,
+                };
+                if let Err(err) = Recorder::write(&mut file.lock().unwrap(), &event) {
+                    warn!("unable to record event: {err}");
+                }
+            }
+
+            // whatever came in while shutting down still makes it in
+            let mut file = file.lock().unwrap();
+            while let Ok(event) = events.try_recv() {
+                if let Err(err) = Recorder::write(&mut file, &event) {
+                    warn!("unable to record event: {err}");
+                }
+            }
+            if let Err(err) = file.flush() {
+                warn!("unable to write recording: {err}");
+            }
+        })
+    }
+}
+
+/// A recorded publish, due this long after the replay started
+struct ReplayedEvent {
+    offset: Duration,
+    topic: String,
+    payload: Vec<u8>,
+}
+
+/// Publishes a recording again with the original spacing, divided by `speed`.
+/// Takes the place of the real services
+pub struct Replayer {
+    event_bus: Arc<Mutex<EventBus>>,
+    events: Arc<Vec<ReplayedEvent>>,
+    speed: f64,
+}
+
+impl Replayer {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, path: &Path, speed: f64) -> io::Result<Self> {
+        let invalid = |err: String| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid recording {}: {err}", path.display()),
+            )
+        };
+
+        let recorded = fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                serde_json::from_str::<RecordedEvent>(line)
+                    .map(|event| (number + 1, event))
+                    .map_err(|err| invalid(format!("line {}: {err}", number + 1)))
+            })
+            .collect::<io::Result<Vec<(usize, RecordedEvent)>>>()?;
+        let Some(first) = recorded.first().map(|(_, event)| event.ts) else {
+            return Err(invalid("nothing recorded".to_string()));
+        };
+
+        let mut events = vec![];
+        for (number, event) in recorded {
+            if NOT_REPLAYED.contains(&event.topic.as_str()) {
+                continue;
+            }
+            // the panels expect whatever is published to parse
+            if EventBusMessage::parse(event.payload.as_bytes()).is_none() {
+                warn!(
+                    "skipping line {number} of {}, not a message",
+                    path.display()
+                );
+                continue;
+            }
+            let offset = event.ts.saturating_sub(first).max(0) as f64 / 1000.0 / speed;
+            let offset = Duration::try_from_secs_f64(offset)
+                .map_err(|err| invalid(format!("line {number}: {err}")))?;
+            events.push(ReplayedEvent {
+                offset,
+                topic: event.topic,
+                payload: event.payload.into_bytes(),
+            });
+        }
+
+        Ok(Self {
+            event_bus,
+            events: Arc::new(events),
+            speed,
+        })
+    }
+}
+
+impl Runnable for Replayer {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let events = Arc::clone(&self.events);
+        let speed = self.speed;
+        info!("replaying {} events at {speed}x", events.len());
+
+        tokio::spawn(async move {
+            let started = Instant::now();
+            for event in events.iter() {
+                let wait = event.offset.saturating_sub(started.elapsed());
+                if cancellable_sleep(&cancel, wait).await {
+                    return;
+                }
+                event_bus
+                    .lock()
+                    .unwrap()
+                    .publish(&event.topic, event.payload.clone());
+            }
+
+            info!("replay finished");
+            // the dashboard keeps showing where the recording ended
+            cancel.cancelled().await;
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{sync::Notify, time::Instant};
+
+use crate::models::{event_bus_message::EventBusMessage, event_type::EventType};
+
+use super::event_bus::{EventBus, ANY_TOPIC};
+
+/// For state that changes without a message of its own, like a status expiring.
+/// Nothing subscribes to it, publishing any message marks the screen dirty
+pub const DIRTY_TOPIC: &str = "render";
+
+/// Publishes on `DIRTY_TOPIC`. A message like any other, whoever streams every
+/// topic gets it too
+pub fn mark_dirty(event_bus: &EventBus) {
+    event_bus.publish(
+        DIRTY_TOPIC,
+        EventBusMessage::new(DIRTY_TOPIC, EventType::Process, None).format_bytes(),
+    );
+}
+
+/// Caps redraws, bursts of messages in between are drawn in one go
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+/// Nobody is looking, so there's no point in drawing often
+const IDLE_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tells the draw loop when something on screen may have changed, which is
+/// whenever anything gets published on the event bus
+pub struct RenderScheduler {
+    dirty: Arc<Notify>,
+    last_frame: Instant,
+    idle: bool,
+}
+
+impl RenderScheduler {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let dirty = Arc::new(Notify::new());
+        {
+            let dirty = Arc::clone(&dirty);
+            event_bus
+                .lock()
+                .unwrap()
+                .subscribe(ANY_TOPIC, move |_| dirty.notify_one());
+        }
+
+        Self {
+            dirty,
+            last_frame: Instant::now(),
+            idle: false,
+        }
+    }
+
+    /// Resolves once something was published since the last frame. Marks are
+    /// coalesced, many messages still only wake this up once
+    pub async fn dirty(&self) {
+        self.dirty.notified().await;
+    }
+
+    /// Waits out the rest of the frame interval, call right before drawing
+    pub async fn next_frame(&mut self) {
+        let interval = if self.idle {
+            IDLE_FRAME_INTERVAL
+        } else {
+            MIN_FRAME_INTERVAL
+        };
+        tokio::time::sleep_until(self.last_frame + interval).await;
+        self.last_frame = Instant::now();
+    }
+
+    pub fn set_idle(&mut self, idle: bool) {
+        self.idle = idle;
+    }
+}
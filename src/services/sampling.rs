@@ -0,0 +1,99 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    models::{
+        config::SamplingConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+    },
+    utils::duration::parse_duration,
+};
+
+use super::event_bus::EventBus;
+
+/// Published by the app when nobody is looking at the display anymore and when
+/// someone is again, the state is `idle` or `active`
+pub const DISPLAY_TOPIC: &str = "display";
+pub const DISPLAY_IDLE: &str = "idle";
+pub const DISPLAY_ACTIVE: &str = "active";
+
+/// `idle_after` from the config, None if idling on a lack of input is disabled
+pub fn idle_after(config: &SamplingConfig) -> io::Result<Option<Duration>> {
+    config
+        .idle_after
+        .as_ref()
+        .map(|value| {
+            parse_duration(value)
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .ok_or(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid sampling idle_after '{value}'"),
+                ))
+        })
+        .transpose()
+}
+
+/// A polling interval that stretches by `idle_factor` while the display is idle
+#[derive(Clone)]
+pub struct Sampling {
+    interval: Duration,
+    idle_factor: u32,
+    idle: Arc<AtomicBool>,
+}
+
+impl Sampling {
+    /// `interval` is a duration like `2s`, `name` is only used for the error
+    pub fn new(
+        event_bus: &Mutex<EventBus>,
+        name: &str,
+        interval: &str,
+        idle_factor: u32,
+    ) -> io::Result<Self> {
+        let secs = parse_duration(interval)
+            .filter(|secs| *secs > 0)
+            .ok_or(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid {name} sampling interval '{interval}'"),
+            ))?;
+
+        let idle = Arc::new(AtomicBool::new(false));
+        {
+            let idle = Arc::clone(&idle);
+            event_bus
+                .lock()
+                .unwrap()
+                .subscribe(DISPLAY_TOPIC, move |data| {
+                    let msg = EventBusMessage::from_bytes(data);
+                    idle.store(
+                        msg.get_field_string(EventFieldType::State) == DISPLAY_IDLE,
+                        Ordering::Relaxed,
+                    );
+                });
+        }
+
+        Ok(Self {
+            interval: Duration::from_secs(secs),
+            idle_factor: idle_factor.max(1),
+            idle,
+        })
+    }
+
+    /// How long to wait until the next sample
+    pub fn interval(&self) -> Duration {
+        if self.idle.load(Ordering::Relaxed) {
+            // the factor comes from the config, however big it is
+            self.interval
+                .checked_mul(self.idle_factor)
+                .unwrap_or(Duration::MAX)
+        } else {
+            self.interval
+        }
+    }
+}
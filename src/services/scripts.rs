@@ -0,0 +1,456 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{info, trace, warn};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST};
+use serde_json::Value;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        config::ScriptConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage, event_type::EventType, plugin_state::PluginState,
+        time_series::Unit,
+    },
+    traits::runnable::Runnable,
+    utils::duration::parse_duration,
+};
+
+use super::{
+    event_bus::{EventBus, ANY_TOPIC},
+    plugins::{metric_message, status_message},
+};
+
+/// What scripts show with `show` and `gauge`, titled with the script name. The
+/// text is the description, gauges have their ratio as a `Value`
+pub const DISPLAY_TOPIC: &str = "script_display";
+
+/// Messages a script can't keep up with are dropped, so it doesn't fall behind
+const EVENT_QUEUE: usize = 64;
+/// Largest file `read_file` hands to a script
+const MAX_READ: u64 = 1024 * 1024;
+
+/// Scripts publish metrics and statuses on their own topic, the same way plugins
+/// do, see `plugins::topic`
+pub fn topic(name: &str) -> String {
+    format!("script.{name}")
+}
+
+/// Title of the status raised when a script fails or runs out of time
+pub fn script_title(name: &str) -> String {
+    format!("script {name}")
+}
+
+/// Messages published by a call, only sent once it returned. Calls run while
+/// the bus is free, but their events come from a subscriber, where it's locked
+type Outbox = Arc<Mutex<Vec<(String, EventBusMessage)>>>;
+
+struct Script {
+    name: String,
+    topics: Vec<String>,
+    interval: Option<Duration>,
+    timeout: Duration,
+    engine: Engine,
+    ast: AST,
+    outbox: Outbox,
+    /// When the running call has to stop, checked by the engine as it goes
+    deadline: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Script {
+    fn has_fn(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name)
+    }
+
+    /// Calls `name` with `this` bound to the script's state, stopped after the
+    /// timeout. Blocks, so it's run on the blocking pool
+    fn call(&self, state: &mut Dynamic, name: &str, args: impl FuncArgs) -> Result<(), String> {
+        *self.deadline.lock().unwrap() = Some(Instant::now() + self.timeout);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            CallFnOptions::new().eval_ast(false).bind_this_ptr(state),
+            &mut Scope::new(),
+            &self.ast,
+            name,
+            args,
+        );
+        *self.deadline.lock().unwrap() = None;
+
+        result.map(|_| ()).map_err(|err| match *err {
+            EvalAltResult::ErrorTerminated(..) => {
+                format!("{name} took longer than {}s", self.timeout.as_secs())
+            }
+            err => format!("{name}: {err}"),
+        })
+    }
+}
+
+/// Runs user scripts in-process, each in its own task with its own engine. The
+/// engine has no access to processes or the network, and to files only if they
+/// are listed in the script's config. Scripts only get what's registered in
+/// `ScriptService::engine`
+pub struct ScriptService {
+    event_bus: Arc<Mutex<EventBus>>,
+    scripts: Vec<Arc<Script>>,
+}
+
+impl ScriptService {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &[ScriptConfig]) -> io::Result<Self> {
+        let scripts = config
+            .iter()
+            .map(|config| ScriptService::load(config).map(Arc::new))
+            .collect::<Result<Vec<Arc<Script>>, String>>()
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+
+        Ok(Self { event_bus, scripts })
+    }
+
+    /// Compiles the script up front, so syntax errors stop startup
+    fn load(config: &ScriptConfig) -> Result<Script, String> {
+        let duration = |value: &str| {
+            parse_duration(value)
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .ok_or(format!(
+                    "invalid duration '{value}' for script '{}'",
+                    config.name
+                ))
+        };
+        // the topic and status titles are built from it
+        if config.name.is_empty() || config.name.contains('|') {
+            return Err(format!("invalid script name '{}'", config.name));
+        }
+        // a script reacting to what scripts publish would keep calling itself,
+        // or another script back
+        let output = config.topics.iter().find(|name| {
+            *name == ANY_TOPIC || *name == DISPLAY_TOPIC || name.starts_with(&topic(""))
+        });
+        if let Some(output) = output {
+            return Err(format!(
+                "script '{}' can't subscribe to '{output}', scripts publish there",
+                config.name
+            ));
+        }
+
+        let outbox: Outbox = Arc::new(Mutex::new(vec![]));
+        let deadline = Arc::new(Mutex::new(None));
+        let engine = ScriptService::engine(&config.name, &config.files, &outbox, &deadline);
+        let ast = engine
+            .compile_file(PathBuf::from(&config.path))
+            .map_err(|err| format!("unable to load script '{}': {err}", config.name))?;
+
+        Ok(Script {
+            name: config.name.clone(),
+            topics: config.topics.clone(),
+            interval: config.interval.as_deref().map(duration).transpose()?,
+            timeout: duration(&config.timeout)?,
+            engine,
+            ast,
+            outbox,
+            deadline,
+        })
+    }
+
+    /// The whole API scripts get:
+    ///
+    /// - `metric(name, value)` and `metric(name, value, unit)`, shown in the plugins panel
+    /// - `status(title, state, description)`, `state` is `ok`, `warning` or `critical`
+    /// - `show(text)` and `gauge(label, ratio)`, shown in the scripts panel
+    /// - `read_file(path)`, the contents of a file up to 1 MB, one of `files`
+    fn engine(
+        name: &str,
+        files: &[String],
+        outbox: &Outbox,
+        deadline: &Arc<Mutex<Option<Instant>>>,
+    ) -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .set_max_call_levels(32)
+            .set_max_string_size(MAX_READ as usize)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000);
+
+        let deadline = Arc::clone(deadline);
+        engine.on_progress(move |_| {
+            deadline
+                .lock()
+                .unwrap()
+                .filter(|deadline| Instant::now() >= *deadline)
+                .map(|_| Dynamic::UNIT)
+        });
+        let log_name = name.to_string();
+        engine.on_print(move |text| info!("script '{log_name}': {text}"));
+        let log_name = name.to_string();
+        engine
+            .on_debug(move |text, _, position| trace!("script '{log_name}' at {position}: {text}"));
+
+        let own_topic = topic(name);
+        let push = {
+            let outbox = Arc::clone(outbox);
+            move |topic: &str, msg: EventBusMessage| {
+                outbox.lock().unwrap().push((topic.to_string(), msg));
+            }
+        };
+
+        let (topic, send) = (own_topic.clone(), push.clone());
+        engine.register_fn("metric", move |name: &str, value: f64| {
+            send(
+                &topic,
+                metric_message(EventType::Script, name, value, &Unit::Plain),
+            );
+        });
+        let (topic, send) = (own_topic.clone(), push.clone());
+        engine.register_fn("metric", move |name: &str, value: i64| {
+            let value = value as f64;
+            send(
+                &topic,
+                metric_message(EventType::Script, name, value, &Unit::Plain),
+            );
+        });
+        let (topic, send) = (own_topic.clone(), push.clone());
+        engine.register_fn(
+            "metric",
+            move |name: &str, value: f64, unit: &str| -> Result<(), Box<EvalAltResult>> {
+                let unit = serde_json::from_value::<Unit>(Value::from(unit))
+                    .map_err(|_| format!("invalid unit '{unit}'"))?;
+                send(
+                    &topic,
+                    metric_message(EventType::Script, name, value, &unit),
+                );
+                Ok(())
+            },
+        );
+        let (topic, send) = (own_topic, push.clone());
+        engine.register_fn(
+            "status",
+            move |title: &str, state: &str, description: &str| -> Result<(), Box<EvalAltResult>> {
+                let state = serde_json::from_value::<PluginState>(Value::from(state))
+                    .map_err(|_| format!("invalid state '{state}'"))?;
+                send(
+                    &topic,
+                    status_message(EventType::Script, title, &state, description),
+                );
+                Ok(())
+            },
+        );
+
+        let (title, send) = (name.to_string(), push.clone());
+        engine.register_fn("show", move |text: &str| {
+            send(
+                DISPLAY_TOPIC,
+                EventBusMessage::new(
+                    &title,
+                    EventType::Script,
+                    Some(vec![(
+                        EventFieldType::Description,
+                        text.as_bytes().to_vec(),
+                    )]),
+                ),
+            );
+        });
+        let (title, send) = (name.to_string(), push);
+        engine.register_fn("gauge", move |label: &str, ratio: f64| {
+            send(
+                DISPLAY_TOPIC,
+                EventBusMessage::new(
+                    &title,
+                    EventType::Script,
+                    Some(vec![
+                        (EventFieldType::Description, label.as_bytes().to_vec()),
+                        (
+                            EventFieldType::Value,
+                            ratio.clamp(0.0, 1.0).to_le_bytes().to_vec(),
+                        ),
+                    ]),
+                ),
+            );
+        });
+
+        let files = files.to_vec();
+        engine.register_fn(
+            "read_file",
+            move |path: &str| -> Result<String, Box<EvalAltResult>> {
+                // resolved, so `..` or a link can't get to anything else
+                let resolved = fs::canonicalize(path).map_err(|err| format!("{path}: {err}"))?;
+                let allowed = files
+                    .iter()
+                    .any(|file| fs::canonicalize(file).is_ok_and(|file| file == resolved));
+                if !allowed {
+                    return Err(format!("{path} isn't in the script's files").into());
+                }
+                let too_large = fs::metadata(&resolved)
+                    .map_err(|err| format!("{path}: {err}"))?
+                    .len()
+                    > MAX_READ;
+                if too_large {
+                    return Err(format!("{path} is larger than {MAX_READ} bytes").into());
+                }
+                fs::read_to_string(&resolved).map_err(|err| format!("{path}: {err}").into())
+            },
+        );
+
+        engine
+    }
+
+    /// Runs a call on the blocking pool and publishes whatever it produced. Only
+    /// changes in whether the script works are published as its own status
+    async fn run<A: FuncArgs + Send + 'static>(
+        script: &Arc<Script>,
+        event_bus: &Mutex<EventBus>,
+        state: Dynamic,
+        failing: &mut bool,
+        name: &'static str,
+        args: A,
+    ) -> Dynamic {
+        let call = Arc::clone(script);
+        let (state, result) = match tokio::task::spawn_blocking(move || {
+            let mut state = state;
+            let result = call.call(&mut state, name, args);
+            (state, result)
+        })
+        .await
+        {
+            Ok(done) => done,
+            // a panic in the engine takes the state with it, start over
+            Err(err) => (Dynamic::from_map(Map::new()), Err(err.to_string())),
+        };
+
+        let mut messages = std::mem::take(&mut *script.outbox.lock().unwrap());
+        match &result {
+            Err(err) => warn!("script '{}' failed: {err}", script.name),
+            Ok(()) => trace!(
+                "script '{}': {name} published {}",
+                script.name,
+                messages.len()
+            ),
+        }
+        if result.is_err() || *failing {
+            *failing = result.is_err();
+            let (state, description) = match result {
+                Ok(()) => (PluginState::Ok, "runs fine".to_string()),
+                Err(err) => (PluginState::Critical, err),
+            };
+            messages.push((
+                topic(&script.name),
+                status_message(
+                    EventType::Script,
+                    &script_title(&script.name),
+                    &state,
+                    &description,
+                ),
+            ));
+        }
+
+        let lock = event_bus.lock().unwrap();
+        for (topic, msg) in messages {
+            lock.publish(&topic, msg.format_bytes());
+        }
+        state
+    }
+
+    /// The message as `EventBusMessage::to_json`, with the topic it came from.
+    /// None for payloads that aren't messages
+    fn to_dynamic(topic: &str, data: &[u8]) -> Option<Dynamic> {
+        let mut msg = EventBusMessage::parse(data)?.to_json();
+        msg["topic"] = Value::from(topic);
+        Some(rhai::serde::to_dynamic(msg).unwrap_or_default())
+    }
+
+    async fn run_script(
+        script: Arc<Script>,
+        event_bus: Arc<Mutex<EventBus>>,
+        cancel: CancellationToken,
+    ) {
+        let (sender, mut events) = mpsc::channel(EVENT_QUEUE);
+        let mut subscriptions = vec![];
+        {
+            let mut lock = event_bus.lock().unwrap();
+            for topic in &script.topics {
+                let (sender, name, from) = (sender.clone(), script.name.clone(), topic.to_string());
+                let id = lock.subscribe(topic, move |data| {
+                    if sender.try_send((from.clone(), data)).is_err() {
+                        trace!("script '{name}' is behind, dropped a message on {from}");
+                    }
+                });
+                subscriptions.push((topic.to_string(), id));
+            }
+        }
+
+        let mut state = Dynamic::from_map(Map::new());
+        let mut failing = false;
+        if script.has_fn("init") {
+            state = ScriptService::run(&script, &event_bus, state, &mut failing, "init", ()).await;
+        }
+
+        let on_event = script.has_fn("on_event");
+        let mut tick = script
+            .interval
+            .filter(|_| script.has_fn("on_tick"))
+            .map(tokio::time::interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                Some((topic, data)) = events.recv() => {
+                    if !on_event {
+                        continue;
+                    }
+                    if let Some(msg) = ScriptService::to_dynamic(&topic, &data) {
+                        state = ScriptService::run(
+                            &script, &event_bus, state, &mut failing, "on_event", (msg,),
+                        )
+                        .await;
+                    }
+                }
+                Some(_) = async { Some(tick.as_mut()?.tick().await) } => {
+                    state = ScriptService::run(
+                        &script, &event_bus, state, &mut failing, "on_tick", (),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        let mut lock = event_bus.lock().unwrap();
+        for (topic, id) in subscriptions {
+            lock.unsubscribe(&topic, id);
+        }
+    }
+}
+
+impl Runnable for ScriptService {
+    fn name(&self) -> &'static str {
+        "scripts"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
+        let event_bus = Arc::clone(&self.event_bus);
+        let scripts = self.scripts.clone();
+        info!("starting {} scripts", scripts.len());
+
+        tokio::spawn(async move {
+            let tasks = scripts
+                .into_iter()
+                .map(|script| {
+                    tokio::spawn(ScriptService::run_script(
+                        script,
+                        Arc::clone(&event_bus),
+                        cancel.clone(),
+                    ))
+                })
+                .collect::<Vec<JoinHandle<()>>>();
+
+            for task in tasks {
+                let _ = task.await;
+            }
+            // like the health checks, don't get restarted over and over
+            cancel.cancelled().await;
+        })
+    }
+}
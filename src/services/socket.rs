@@ -1,99 +1,344 @@
 use std::{
     env::temp_dir,
-    path::Path,
+    io,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    thread::sleep,
-    time::{self, Duration},
+    time::Duration,
 };
 
+use log::warn;
 use tokio::sync::Mutex as TokioMutex;
-use tokio::{fs, io::AsyncReadExt, net::UnixListener};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
 
 use crate::models::{
-    event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
-    event_type::EventType, socket_message::SocketMessage,
+    event_bus_field_type::EventFieldType,
+    event_bus_message::EventBusMessage,
+    event_type::EventType,
+    socket_message::{HeartbeatMessage, SocketCommand, SocketMessage, SocketQuery, SocketRequest},
+    status_action::StatusAction,
 };
 use crate::traits::runnable::Runnable;
+use crate::utils::{
+    duration::{parse_duration, timestamp_after},
+    task::cancellable_sleep,
+};
 
-use super::event_bus::EventBus;
+use super::{
+    event_bus::EventBus,
+    heartbeat, maintenance,
+    query::{Queries, TopicStream},
+};
 
 pub const EVENT_TOPIC: &str = "socket_service";
 pub const SOCKET_DONE_TEXT: &str = "done";
-
+/// Statuses starting with this stay on the panel, but end the job in the history
+pub const SOCKET_FAILED_TEXT: &str = "failed";
+pub const SOCKET_NAME: &str = "server-tui.sock";
+/// Acknowledge, dismiss and snooze requests, titled with the status entry. The
+/// state is the `StatusAction`, snoozes carry their end as the timestamp
+pub const STATUS_REQUEST_TOPIC: &str = "status_request";
+/// Asks the app to write a snapshot of the dashboard. The state is the
+/// `SnapshotFormat`, the description the absolute path to write to, which
+/// must not exist yet
+pub const SNAPSHOT_REQUEST_TOPIC: &str = "snapshot_request";
 pub struct SocketService {
     listener: Arc<TokioMutex<UnixListener>>,
     event_bus: Arc<Mutex<EventBus>>,
+    queries: Queries,
 }
 
 impl SocketService {
-    pub async fn new(event_bus: Arc<Mutex<EventBus>>, socket_name: &str) -> Self {
-        Self {
-            listener: Arc::new(TokioMutex::new(
-                SocketService::init_socket(socket_name).await,
-            )),
+    /// Expects the caller to own `socket_path` through an `InstanceLock`, any
+    /// leftovers from a dead instance must already be cleaned up
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, socket_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            listener: Arc::new(TokioMutex::new(UnixListener::bind(socket_path)?)),
+            queries: Queries::new(Arc::clone(&event_bus)),
             event_bus,
+        })
+    }
+
+    pub fn socket_path(socket_name: &str) -> PathBuf {
+        temp_dir().join(socket_name)
+    }
+
+    fn parse_request(buffer: Vec<u8>) -> Option<SocketRequest> {
+        let string = String::from_utf8(buffer).ok()?;
+        // remove any additional zeros from the buffer
+        let string = string.trim_end_matches(char::from(0));
+        match serde_json::from_str::<SocketRequest>(string) {
+            Ok(request) => Some(request),
+            Err(err) => {
+                warn!("invalid socket message '{string}': {err}");
+                None
+            }
         }
     }
 
-    async fn init_socket(socket_name: &str) -> UnixListener {
-        let dir = temp_dir();
-        let bind_path = dir.join(socket_name);
-        if Path::new(&bind_path).exists() {
-            fs::remove_file(&bind_path)
-                .await
-                .expect("unable to remove old socket on init");
+    async fn process_message(
+        stream: UnixStream,
+        buffer: Vec<u8>,
+        event_bus: Arc<Mutex<EventBus>>,
+        queries: Queries,
+        cancel: CancellationToken,
+    ) {
+        match SocketService::parse_request(buffer) {
+            Some(SocketRequest::Status(msg)) => SocketService::process_status(msg, event_bus),
+            Some(SocketRequest::Command(cmd)) => SocketService::process_command(cmd, event_bus),
+            Some(SocketRequest::Heartbeat(msg)) => SocketService::process_heartbeat(msg, event_bus),
+            Some(SocketRequest::Query(SocketQuery::Subscribe { topic })) => {
+                SocketService::stream_topic(stream, &topic, event_bus, cancel).await
+            }
+            Some(SocketRequest::Query(query)) => {
+                let reply = queries.ask(&query).await;
+                SocketService::reply(stream, reply).await;
+            }
+            None => (),
         }
+    }
+
+    async fn reply(mut stream: UnixStream, reply: String) {
+        if let Err(err) = stream.write_all(format!("{reply}\n").as_bytes()).await {
+            warn!("unable to answer socket query: {err}");
+        }
+    }
+
+    /// Writes every message on `topic` until the client hangs up or we shut down
+    async fn stream_topic(
+        mut stream: UnixStream,
+        topic: &str,
+        event_bus: Arc<Mutex<EventBus>>,
+        cancel: CancellationToken,
+    ) {
+        let mut messages = TopicStream::subscribe(event_bus, topic);
+        let mut buffer = [0u8; 64];
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                // anything but more input means the client is gone
+                read = stream.read(&mut buffer) => if !matches!(read, Ok(count) if count > 0) {
+                    break;
+                },
+                msg = messages.next() => {
+                    // the client fell behind and was unsubscribed
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    if stream.write_all(format!("{msg}\n").as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
 
-        UnixListener::bind(&bind_path).expect("unable to initialize socket listener")
+    /// Titles from clients end up in bus messages, where `|` separates the parts
+    fn valid_title(title: &str) -> bool {
+        if title.contains('|') {
+            warn!("ignoring socket message for '{title}', titles can't contain '|'");
+            return false;
+        }
+        true
     }
 
-    fn process_message(buffer: Vec<u8>, event_bus: Arc<Mutex<EventBus>>) {
-        if let Ok(string) = String::from_utf8(buffer) {
-            let msg: SocketMessage = serde_json::from_str(
-                // remove any additional zeros from the buffer
-                string.trim_end_matches(char::from(0)),
+    fn process_status(msg: SocketMessage, event_bus: Arc<Mutex<EventBus>>) {
+        if !SocketService::valid_title(&msg.title) {
+            return;
+        }
+        event_bus.lock().unwrap().publish(
+            EVENT_TOPIC,
+            EventBusMessage::new(
+                &msg.title,
+                EventType::Socket,
+                Some(vec![(EventFieldType::Description, msg.status.into_bytes())]),
             )
-            .unwrap();
-
-            event_bus.lock().unwrap().publish(
-                EVENT_TOPIC,
-                EventBusMessage::new(
-                    &msg.title,
-                    EventType::Socket,
-                    Some(vec![(EventFieldType::Description, msg.status.into_bytes())]),
-                )
-                .format_bytes(),
-            );
+            .format_bytes(),
+        );
+    }
+
+    fn process_heartbeat(msg: HeartbeatMessage, event_bus: Arc<Mutex<EventBus>>) {
+        if !SocketService::valid_title(&msg.heartbeat) {
+            return;
         }
+        let mut fields = vec![];
+        for (field, value) in [
+            (EventFieldType::Interval, &msg.every),
+            (EventFieldType::Grace, &msg.grace),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            let Some(secs) = parse_duration(value).and_then(|secs| i64::try_from(secs).ok()) else {
+                warn!(
+                    "invalid heartbeat duration '{value}' for '{}'",
+                    msg.heartbeat
+                );
+                return;
+            };
+            fields.push((field, secs.to_le_bytes().to_vec()));
+        }
+
+        event_bus.lock().unwrap().publish(
+            heartbeat::REQUEST_TOPIC,
+            EventBusMessage::new(&msg.heartbeat, EventType::Heartbeat, Some(fields)).format_bytes(),
+        );
+    }
+
+    fn process_command(cmd: SocketCommand, event_bus: Arc<Mutex<EventBus>>) {
+        if let SocketCommand::StatusAck { title }
+        | SocketCommand::StatusDismiss { title }
+        | SocketCommand::StatusSnooze { title, .. } = &cmd
+        {
+            if !SocketService::valid_title(title) {
+                return;
+            }
+        }
+
+        let (topic, msg) = match cmd {
+            SocketCommand::MaintenanceStart {
+                duration,
+                reason,
+                mode,
+            } => {
+                let Some(until) = parse_duration(&duration).and_then(timestamp_after) else {
+                    warn!("invalid maintenance duration '{duration}'");
+                    return;
+                };
+
+                (
+                    maintenance::REQUEST_TOPIC,
+                    EventBusMessage::new(
+                        maintenance::REQUEST_START,
+                        EventType::Maintenance,
+                        Some(vec![
+                            (EventFieldType::Timestamp, until.to_le_bytes().to_vec()),
+                            (EventFieldType::Description, reason.into_bytes()),
+                            (EventFieldType::State, mode.to_string().as_bytes().to_vec()),
+                        ]),
+                    ),
+                )
+            }
+            SocketCommand::MaintenanceEnd => (
+                maintenance::REQUEST_TOPIC,
+                EventBusMessage::new(maintenance::REQUEST_END, EventType::Maintenance, None),
+            ),
+            SocketCommand::StatusAck { title } => (
+                STATUS_REQUEST_TOPIC,
+                SocketService::status_request(&title, StatusAction::Acknowledge, vec![]),
+            ),
+            SocketCommand::StatusDismiss { title } => (
+                STATUS_REQUEST_TOPIC,
+                SocketService::status_request(&title, StatusAction::Dismiss, vec![]),
+            ),
+            SocketCommand::StatusSnooze { title, duration } => {
+                let mut fields = vec![];
+                if let Some(duration) = duration {
+                    let Some(until) = parse_duration(&duration).and_then(timestamp_after) else {
+                        warn!("invalid snooze duration '{duration}' for '{title}'");
+                        return;
+                    };
+                    fields.push((EventFieldType::Timestamp, until.to_le_bytes().to_vec()));
+                }
+                (
+                    STATUS_REQUEST_TOPIC,
+                    SocketService::status_request(&title, StatusAction::Snooze, fields),
+                )
+            }
+            SocketCommand::Snapshot { format, path } => {
+                // relative paths would end up in the instance's working directory
+                if !Path::new(&path).is_absolute() {
+                    warn!("snapshot path '{path}' is not absolute");
+                    return;
+                }
+                (
+                    SNAPSHOT_REQUEST_TOPIC,
+                    EventBusMessage::new(
+                        SNAPSHOT_REQUEST_TOPIC,
+                        EventType::Socket,
+                        Some(vec![
+                            (
+                                EventFieldType::State,
+                                format.to_string().as_bytes().to_vec(),
+                            ),
+                            (EventFieldType::Description, path.into_bytes()),
+                        ]),
+                    ),
+                )
+            }
+        };
+
+        event_bus.lock().unwrap().publish(topic, msg.format_bytes());
+    }
+
+    fn status_request(
+        title: &str,
+        action: StatusAction,
+        mut fields: Vec<(EventFieldType, Vec<u8>)>,
+    ) -> EventBusMessage {
+        fields.push((
+            EventFieldType::State,
+            action.to_string().as_bytes().to_vec(),
+        ));
+        EventBusMessage::new(title, EventType::Socket, Some(fields))
     }
 
     // TODO: needs to handle errors / bad input / kick out clients
     async fn listen_on_socket(
         listener: Arc<TokioMutex<UnixListener>>,
         event_bus: Arc<Mutex<EventBus>>,
+        queries: Queries,
+        cancel: CancellationToken,
     ) {
-        while let Ok((mut stream, _)) = listener.lock().await.accept().await {
+        let listener = listener.lock().await;
+        loop {
+            let mut stream = tokio::select! {
+                _ = cancel.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(_) => break,
+                },
+            };
+
             let mut buffer = vec![0u8; 1024];
             {
                 let event_bus = Arc::clone(&event_bus);
+                let queries = queries.clone();
+                let cancel = cancel.clone();
                 tokio::spawn(async move {
                     match stream.read(&mut buffer).await {
                         Err(err) => println!("err: {err}"),
-                        Ok(_) => SocketService::process_message(buffer, event_bus),
+                        Ok(_) => {
+                            SocketService::process_message(
+                                stream, buffer, event_bus, queries, cancel,
+                            )
+                            .await
+                        }
                     }
                 });
             }
-            sleep(Duration::from_millis(100));
+            if cancellable_sleep(&cancel, Duration::from_millis(100)).await {
+                break;
+            }
         }
     }
 }
 
 impl Runnable for SocketService {
-    fn run(&self) {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()> {
         let listener = Arc::clone(&self.listener);
         let event_bus = Arc::clone(&self.event_bus);
+        let queries = self.queries.clone();
         tokio::spawn(async move {
-            SocketService::listen_on_socket(listener, event_bus).await;
-        });
+            SocketService::listen_on_socket(listener, event_bus, queries, cancel).await;
+        })
     }
 }
@@ -0,0 +1,166 @@
+use std::{
+    any::Any,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use tokio::{task::JoinHandle, time::timeout};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::{
+        event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
+        event_type::EventType, service_state::ServiceState,
+    },
+    traits::runnable::Runnable,
+    utils::task::cancellable_sleep,
+};
+
+use super::event_bus::EventBus;
+
+pub const EVENT_TOPIC: &str = "supervisor";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// a service that kept running for this long before failing gets its backoff reset
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+struct Supervised {
+    service: Arc<dyn Runnable>,
+    monitor: Option<JoinHandle<()>>,
+}
+
+/// Owns every service task, restarting them with a backoff if they panic or
+/// return before being cancelled
+pub struct Supervisor {
+    event_bus: Arc<Mutex<EventBus>>,
+    cancel: CancellationToken,
+    services: Vec<Supervised>,
+}
+
+impl Supervisor {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        Self {
+            event_bus,
+            cancel: CancellationToken::new(),
+            services: vec![],
+        }
+    }
+
+    pub fn add(&mut self, service: Box<dyn Runnable>) {
+        self.services.push(Supervised {
+            service: Arc::from(service),
+            monitor: None,
+        });
+    }
+
+    /// Starts every service that isn't already running
+    pub fn start_all(&mut self) {
+        for supervised in self.services.iter_mut() {
+            if supervised
+                .monitor
+                .as_ref()
+                .is_some_and(|monitor| !monitor.is_finished())
+            {
+                continue;
+            }
+
+            supervised.monitor = Some(Supervisor::monitor(
+                Arc::clone(&supervised.service),
+                self.cancel.child_token(),
+                Arc::clone(&self.event_bus),
+            ));
+        }
+    }
+
+    /// Cancels all services and waits a little while for them to wind down
+    pub async fn shutdown(&mut self) {
+        self.cancel.cancel();
+        for supervised in self.services.iter_mut() {
+            let Some(monitor) = supervised.monitor.take() else {
+                continue;
+            };
+
+            if timeout(SHUTDOWN_TIMEOUT, monitor).await.is_err() {
+                warn!(
+                    "service {} didn't stop within {:?}",
+                    supervised.service.name(),
+                    SHUTDOWN_TIMEOUT
+                );
+            }
+        }
+    }
+
+    fn monitor(
+        service: Arc<dyn Runnable>,
+        cancel: CancellationToken,
+        event_bus: Arc<Mutex<EventBus>>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let name = service.name();
+            let mut backoff = INITIAL_BACKOFF;
+            // kept around after a restart, so the panel can still tell why it happened
+            let mut last_error: Option<String> = None;
+
+            loop {
+                Supervisor::publish(&event_bus, name, ServiceState::Running, &last_error);
+                let started = Instant::now();
+                let result = service.start(cancel.clone()).await;
+
+                if cancel.is_cancelled() {
+                    info!("service {name} stopped");
+                    Supervisor::publish(&event_bus, name, ServiceState::Stopped, &last_error);
+                    return;
+                }
+
+                let reason = match result {
+                    Ok(_) => "exited unexpectedly".to_string(),
+                    Err(err) if err.is_panic() => Supervisor::panic_message(err.into_panic()),
+                    Err(err) => err.to_string(),
+                };
+                if started.elapsed() >= STABLE_AFTER {
+                    backoff = INITIAL_BACKOFF;
+                }
+                error!("service {name} failed: {reason}, restarting in {backoff:?}");
+
+                last_error = Some(reason);
+                Supervisor::publish(&event_bus, name, ServiceState::Restarting, &last_error);
+                if cancellable_sleep(&cancel, backoff).await {
+                    Supervisor::publish(&event_bus, name, ServiceState::Stopped, &last_error);
+                    return;
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        })
+    }
+
+    fn panic_message(payload: Box<dyn Any + Send>) -> String {
+        if let Some(msg) = payload.downcast_ref::<&str>() {
+            return format!("panicked: {msg}");
+        }
+
+        match payload.downcast_ref::<String>() {
+            Some(msg) => format!("panicked: {msg}"),
+            None => "panicked".to_string(),
+        }
+    }
+
+    fn publish(
+        event_bus: &Arc<Mutex<EventBus>>,
+        name: &str,
+        state: ServiceState,
+        error: &Option<String>,
+    ) {
+        let mut fields = vec![(EventFieldType::State, state.to_string().as_bytes().to_vec())];
+        if let Some(error) = error {
+            fields.push((EventFieldType::Error, error.as_bytes().to_vec()));
+        }
+
+        event_bus.lock().unwrap().publish(
+            EVENT_TOPIC,
+            EventBusMessage::new(name, EventType::Service, Some(fields)).format_bytes(),
+        );
+    }
+}
@@ -1,3 +1,11 @@
-pub trait Runnable {
-    fn run(&self);
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub trait Runnable: Send + Sync {
+    /// Unique name, used by the supervisor and the services panel
+    fn name(&self) -> &'static str;
+
+    /// Spawns the service task. The task has to return once `cancel` is triggered,
+    /// and may be started again after it stopped or panicked
+    fn start(&self, cancel: CancellationToken) -> JoinHandle<()>;
 }
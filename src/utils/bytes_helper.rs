@@ -5,3 +5,7 @@ pub fn bytes_to_f64(bytes: Vec<u8>) -> f64 {
 pub fn bytes_to_i64(bytes: Vec<u8>) -> i64 {
     i64::from_le_bytes(bytes.try_into().unwrap())
 }
+
+pub fn bytes_to_u64(bytes: Vec<u8>) -> u64 {
+    u64::from_le_bytes(bytes.try_into().unwrap())
+}
@@ -0,0 +1,58 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use log::info;
+
+use crate::models::config::Config;
+
+pub const CONFIG_ENV: &str = "SERVER_TUI_CONFIG";
+
+/// Loads the config from `$SERVER_TUI_CONFIG`, falling back to
+/// `$XDG_CONFIG_HOME/server-tui/config.toml`. A missing file gives the defaults
+pub fn load() -> io::Result<Config> {
+    let Some(path) = config_path() else {
+        return Ok(Config::default());
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(res) => res,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(err) => return Err(err),
+    };
+
+    info!("loading config from {}", path.display());
+    toml::from_str(&contents).map_err(|err| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid config {}: {err}", path.display()),
+        )
+    })
+}
+
+/// Where state that should survive a restart lives, under
+/// `$XDG_STATE_HOME/server-tui`. The directory is created if needed
+pub fn state_path(file_name: &str) -> Option<PathBuf> {
+    let state_home = match env::var("XDG_STATE_HOME") {
+        Ok(res) if !res.is_empty() => PathBuf::from(res),
+        _ => PathBuf::from(env::var("HOME").ok()?).join(".local/state"),
+    };
+
+    let dir = state_home.join("server-tui");
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(file_name))
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_home = match env::var("XDG_CONFIG_HOME") {
+        Ok(res) if !res.is_empty() => PathBuf::from(res),
+        _ => PathBuf::from(env::var("HOME").ok()?).join(".config"),
+    };
+    Some(config_home.join("server-tui").join("config.toml"))
+}
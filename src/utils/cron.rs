@@ -0,0 +1,125 @@
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Timelike};
+
+/// A five field cron expression (`minute hour day-of-month month day-of-week`),
+/// supporting `*`, lists (`1,3`), ranges (`1-5`) and steps (`*/15`, `0-30/10`).
+/// Like cron, a day matches either day field when both are restricted
+pub struct CronSchedule {
+    fields: [Vec<u32>; 5],
+    /// Neither day field starts with `*`, so they're ORed instead of ANDed
+    either_day: bool,
+}
+
+// day-of-week allows 7 as sunday as well, it's wrapped around after parsing
+const FIELD_RANGES: [(u32, u32); 5] = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 7)];
+
+impl CronSchedule {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let parts = input.split_whitespace().collect::<Vec<&str>>();
+        if parts.len() != 5 {
+            return Err(format!(
+                "expected 5 fields in '{input}', got {}",
+                parts.len()
+            ));
+        }
+
+        let mut fields: [Vec<u32>; 5] = Default::default();
+        for (i, part) in parts.iter().enumerate() {
+            fields[i] = CronSchedule::parse_field(part, FIELD_RANGES[i])
+                .ok_or(format!("invalid field '{part}' in '{input}'"))?;
+        }
+        fields[4].iter_mut().for_each(|day| *day %= 7);
+
+        Ok(Self {
+            fields,
+            either_day: !parts[2].starts_with('*') && !parts[4].starts_with('*'),
+        })
+    }
+
+    fn parse_field(field: &str, (min, max): (u32, u32)) -> Option<Vec<u32>> {
+        let mut values = vec![];
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>().ok()?),
+                None => (item, 1),
+            };
+
+            let (start, end) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                    None => {
+                        let value = range.parse().ok()?;
+                        (value, value)
+                    }
+                },
+            };
+
+            if step == 0 || start < min || end > max || start > end {
+                return None;
+            }
+            values.extend((start..=end).step_by(step as usize));
+        }
+
+        Some(values)
+    }
+
+    pub fn matches<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        let values = [
+            time.minute(),
+            time.hour(),
+            time.day(),
+            time.month(),
+            time.weekday().num_days_from_sunday(),
+        ];
+        let [minute, hour, day, month, weekday] =
+            [0, 1, 2, 3, 4].map(|i| self.fields[i].contains(&values[i]));
+
+        let day_matches = match self.either_day {
+            true => day || weekday,
+            false => day && weekday,
+        };
+        minute && hour && month && day_matches
+    }
+
+    /// Finds the most recent start within `lookback` of `now`, useful to tell
+    /// if a window of that length is currently open
+    pub fn last_start(&self, now: DateTime<Local>, lookback: Duration) -> Option<DateTime<Local>> {
+        let now = now.with_second(0)?.with_nanosecond(0)?;
+        (0..=lookback.num_minutes())
+            .map(|minutes| now - Duration::minutes(minutes))
+            .find(|time| self.matches(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn restricted_day_fields_are_ored() {
+        let schedule = CronSchedule::parse("0 0 1 * 1").unwrap();
+        // the 1st, a Thursday
+        assert!(schedule.matches(&at(2026, 10, 1)));
+        // a Monday
+        assert!(schedule.matches(&at(2026, 10, 19)));
+        // a Tuesday, not the 1st
+        assert!(!schedule.matches(&at(2026, 10, 20)));
+
+        // with one of them unrestricted, only the other counts
+        let schedule = CronSchedule::parse("0 0 * * 1").unwrap();
+        assert!(!schedule.matches(&at(2026, 10, 1)));
+        assert!(schedule.matches(&at(2026, 10, 19)));
+        let schedule = CronSchedule::parse("0 0 1 * *").unwrap();
+        assert!(schedule.matches(&at(2026, 10, 1)));
+        assert!(!schedule.matches(&at(2026, 10, 19)));
+    }
+}
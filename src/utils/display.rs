@@ -0,0 +1,29 @@
+use std::fs;
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+/// True if there are connected screens and every one of them is powered off
+/// through DPMS. Headless machines and terminals over SSH never count as off
+pub fn screens_off() -> bool {
+    let Ok(entries) = fs::read_dir(DRM_PATH) else {
+        return false;
+    };
+
+    let mut connected = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let read = |file: &str| {
+            fs::read_to_string(path.join(file))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        if read("status") != "connected" {
+            continue;
+        }
+        connected += 1;
+        if read("dpms") != "Off" {
+            return false;
+        }
+    }
+    connected > 0
+}
@@ -0,0 +1,61 @@
+/// Formats seconds as the two most significant units, e.g. `3d 4h`, `12m 5s`
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = secs % 86_400 / 3_600;
+    let minutes = secs % 3_600 / 60;
+    let seconds = secs % 60;
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Parses durations like `90s`, `15m`, `2h` or `1h30m`, a bare number is seconds
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input = input.trim();
+    if let Ok(secs) = input.parse::<u64>() {
+        return Some(secs);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'd' => 86_400,
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        // overflowing input is as invalid as a bad unit, it comes from the socket
+        let secs = number.parse::<u64>().ok()?.checked_mul(unit)?;
+        total = secs.checked_add(total)?;
+        number.clear();
+    }
+
+    // trailing digits without a unit, like `1h30`
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total)
+}
+
+/// Unix timestamp `secs` from now, `None` if that's past any date that can be
+/// shown. Durations from the socket go through this
+pub fn timestamp_after(secs: u64) -> Option<i64> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| chrono::Utc::now().timestamp().checked_add(secs))
+        .filter(|until| chrono::DateTime::from_timestamp(*until, 0).is_some())
+}
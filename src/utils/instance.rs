@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process,
+};
+
+use log::{info, warn};
+
+pub enum InstanceState {
+    /// Nothing owns the socket, we're free to bind it
+    Free,
+    /// Another instance answers on the socket, holds the PID if we know it
+    Running(Option<u32>),
+    /// Socket or PID file is left behind by an instance that is no longer alive
+    Stale,
+}
+
+/// Removes the socket and PID file when dropped, so only hold on to this for as
+/// long as we own the socket
+pub struct InstanceLock {
+    socket_path: PathBuf,
+    pid_path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Claims ownership of the socket path, cleaning up after a dead instance if needed.
+    /// Fails with `ErrorKind::AddrInUse` if another instance is alive
+    pub fn acquire(socket_path: &Path) -> io::Result<Self> {
+        let pid_path = pid_path(socket_path);
+
+        match probe(socket_path) {
+            InstanceState::Running(pid) => {
+                return Err(io::Error::new(
+                    ErrorKind::AddrInUse,
+                    match pid {
+                        Some(pid) => format!("server-tui is already running with PID {pid}"),
+                        None => "server-tui is already running".to_string(),
+                    },
+                ))
+            }
+            InstanceState::Stale => {
+                info!("removing stale socket at {}", socket_path.display());
+                remove_if_exists(socket_path)?;
+                remove_if_exists(&pid_path)?;
+            }
+            InstanceState::Free => (),
+        }
+
+        fs::write(&pid_path, process::id().to_string())?;
+
+        Ok(Self {
+            socket_path: socket_path.to_path_buf(),
+            pid_path,
+        })
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        for path in [&self.socket_path, &self.pid_path] {
+            if let Err(err) = remove_if_exists(path) {
+                warn!("unable to remove {} on shutdown: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Figures out if someone owns the socket. We try to connect first, as a socket
+/// that accepts connections is alive no matter what the PID file says
+pub fn probe(socket_path: &Path) -> InstanceState {
+    let pid = read_pid(&pid_path(socket_path));
+
+    if UnixStream::connect(socket_path).is_ok() {
+        return InstanceState::Running(pid);
+    }
+
+    match pid {
+        // the owner is alive but not accepting connections (yet), don't steal the socket
+        Some(pid) if pid != process::id() && is_alive(pid) => InstanceState::Running(Some(pid)),
+        Some(_) => InstanceState::Stale,
+        None if socket_path.exists() => InstanceState::Stale,
+        None => InstanceState::Free,
+    }
+}
+
+pub fn pid_path(socket_path: &Path) -> PathBuf {
+    socket_path.with_extension("pid")
+}
+
+fn read_pid(pid_path: &Path) -> Option<u32> {
+    fs::read_to_string(pid_path).ok()?.trim().parse().ok()
+}
+
+fn is_alive(pid: u32) -> bool {
+    Path::new(&format!("/proc/{pid}")).exists()
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -1,9 +1,20 @@
-use ratatui::layout::{Constraint, Direction, Flex, Layout};
+use ratatui::layout::{Constraint, Direction, Flex, Layout, Rect};
 
 pub fn make_layout(dir: Direction, count: u16) -> Layout {
-    let percentage = 100 / count;
+    let percentage = 100 / count.max(1);
     Layout::default()
         .direction(dir)
         .flex(Flex::Center)
         .constraints(vec![Constraint::Percentage(percentage); count as usize])
 }
+
+/// A rect in the middle of `area`, used for popups
+pub fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Percentage(percent_y)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
@@ -1,2 +1,9 @@
 pub mod bytes_helper;
+pub mod config;
+pub mod cron;
+pub mod display;
+pub mod duration;
+pub mod instance;
 pub mod layout;
+pub mod snapshot;
+pub mod task;
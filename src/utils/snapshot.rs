@@ -0,0 +1,215 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// What the page falls back to for cells without a colour, like a dark terminal
+const DEFAULT_FG: &str = "#d0d0d0";
+const DEFAULT_BG: &str = "#000000";
+
+/// Rows of cells, skipping the ones covered by a wide character before them
+fn rows(buf: &Buffer) -> Vec<Vec<&Cell>> {
+    let width = buf.area.width as usize;
+    buf.content()
+        .chunks(width.max(1))
+        .map(|row| {
+            let mut cells = vec![];
+            let mut covered = 0;
+            for cell in row {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                covered = cell.symbol().width().saturating_sub(1);
+                if !cell.skip {
+                    cells.push(cell);
+                }
+            }
+            cells
+        })
+        .collect()
+}
+
+/// The screen as plain text, without trailing whitespace
+pub fn to_text(buf: &Buffer) -> String {
+    rows(buf)
+        .into_iter()
+        .map(|row| {
+            let line = row.iter().map(|cell| cell.symbol()).collect::<String>();
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// A page with the screen in a `<pre>`, every run of equally styled cells in a
+/// span with inline styles, so it can be opened or attached on its own
+pub fn to_html(buf: &Buffer, title: &str) -> String {
+    let mut body = String::new();
+    for row in rows(buf) {
+        let mut run = String::new();
+        let mut run_style = None;
+        for cell in row {
+            let style = css(cell);
+            if run_style.as_ref() != Some(&style) {
+                push_span(&mut body, &run, run_style.as_deref());
+                run.clear();
+                run_style = Some(style);
+            }
+            run.push_str(cell.symbol());
+        }
+        push_span(&mut body, &run, run_style.as_deref());
+        body.push('\n');
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>body {{ background: {DEFAULT_BG}; color: {DEFAULT_FG}; }} \
+         pre {{ font-family: monospace; line-height: 1.2; }}</style>\n\
+         </head>\n<body>\n<pre>\n{body}</pre>\n</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// Writes next to `path` first and moves it in place, so nobody reads half a file
+pub fn write(path: &Path, contents: &str) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, path)
+}
+
+/// Like `write`, but fails instead of replacing anything already at `path`
+pub fn write_new(path: &Path, contents: &str) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&partial)?
+        .write_all(contents.as_bytes())?;
+    // unlike a rename, linking refuses to replace the target
+    let linked = fs::hard_link(&partial, path);
+    fs::remove_file(&partial)?;
+    linked
+}
+
+fn push_span(body: &mut String, text: &str, style: Option<&str>) {
+    if text.is_empty() {
+        return;
+    }
+    match style {
+        Some(style) if !style.is_empty() => {
+            body.push_str(&format!("<span style=\"{style}\">{}</span>", escape(text)))
+        }
+        _ => body.push_str(&escape(text)),
+    }
+}
+
+fn css(cell: &Cell) -> String {
+    let (mut fg, mut bg) = (color(cell.fg), color(cell.bg));
+    if cell.modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (
+            Some(bg.unwrap_or(DEFAULT_BG.to_string())),
+            Some(fg.unwrap_or(DEFAULT_FG.to_string())),
+        );
+    }
+
+    let mut style = vec![];
+    if let Some(fg) = fg {
+        style.push(format!("color:{fg}"));
+    }
+    if let Some(bg) = bg {
+        style.push(format!("background:{bg}"));
+    }
+    for (modifier, property) in [
+        (Modifier::BOLD, "font-weight:bold"),
+        (Modifier::DIM, "opacity:0.6"),
+        (Modifier::ITALIC, "font-style:italic"),
+        (Modifier::UNDERLINED, "text-decoration:underline"),
+        (Modifier::CROSSED_OUT, "text-decoration:line-through"),
+    ] {
+        if cell.modifier.contains(modifier) {
+            style.push(property.to_string());
+        }
+    }
+    style.join(";")
+}
+
+/// CSS colour for a terminal colour, None for the terminal's default
+fn color(color: Color) -> Option<String> {
+    let hex = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Indexed(index) => return Some(indexed(index)),
+        Color::Black => "#000000",
+        Color::Red => "#cd0000",
+        Color::Green => "#00cd00",
+        Color::Yellow => "#cdcd00",
+        Color::Blue => "#0000ee",
+        Color::Magenta => "#cd00cd",
+        Color::Cyan => "#00cdcd",
+        Color::Gray => "#e5e5e5",
+        Color::DarkGray => "#7f7f7f",
+        Color::LightRed => "#ff0000",
+        Color::LightGreen => "#00ff00",
+        Color::LightYellow => "#ffff00",
+        Color::LightBlue => "#5c5cff",
+        Color::LightMagenta => "#ff00ff",
+        Color::LightCyan => "#00ffff",
+        Color::White => "#ffffff",
+    };
+    Some(hex.to_string())
+}
+
+/// The xterm 256 colour palette
+fn indexed(index: u8) -> String {
+    const BASE: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Gray,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+    ];
+    match index {
+        0..=15 => color(BASE[index as usize]).unwrap_or_default(),
+        16..=231 => {
+            let index = index - 16;
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(index / 36),
+                level(index / 6 % 6),
+                level(index % 6)
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            format!("#{gray:02x}{gray:02x}{gray:02x}")
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
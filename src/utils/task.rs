@@ -0,0 +1,11 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+/// Sleeps for `duration` unless `cancel` fires first, returns true if cancelled
+pub async fn cancellable_sleep(cancel: &CancellationToken, duration: Duration) -> bool {
+    tokio::select! {
+        _ = cancel.cancelled() => true,
+        _ = tokio::time::sleep(duration) => false,
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{Local, TimeZone};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, WidgetRef},
+};
+
+use crate::{
+    models::{cert_state::CertState, event_bus_field_type::EventFieldType},
+    services::event_bus::EventBus,
+    utils::bytes_helper::bytes_to_i64,
+};
+
+use super::{controllers::certs::CertsController, theme::Theme};
+
+const THEME_KEY: &str = "certs";
+
+/// Days until expiry for every watched certificate
+pub struct CertsWidget {
+    controller: CertsController,
+    theme: Arc<Theme>,
+}
+
+impl CertsWidget {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, theme: Arc<Theme>) -> Self {
+        Self {
+            controller: CertsController::new(event_bus),
+            theme,
+        }
+    }
+}
+
+impl WidgetRef for CertsWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Certificates ").style(self.theme.style(THEME_KEY, "title")));
+
+        let now = chrono::Utc::now().timestamp();
+        let mut lines: Vec<Line> = vec![];
+        for msg in self.controller.certs_lock().iter() {
+            let state = CertState::from_string(&msg.get_field_string(EventFieldType::State));
+            let state_style = match state {
+                CertState::Valid => self.theme.style(THEME_KEY, "ok"),
+                CertState::Expiring => self.theme.style(THEME_KEY, "warning"),
+                CertState::Critical => self.theme.style(THEME_KEY, "error"),
+            };
+
+            let mut spans = vec![Span::styled(
+                format!("{} ", msg.title()),
+                self.theme.style(THEME_KEY, "heading"),
+            )];
+            if msg.has_field(EventFieldType::Timestamp) {
+                let not_after = bytes_to_i64(msg.get_field(EventFieldType::Timestamp));
+                spans.push(Span::styled(
+                    format!("{}d ", (not_after - now).div_euclid(86_400)),
+                    state_style,
+                ));
+                spans.push(Span::styled(
+                    format!(
+                        "{} ",
+                        Local
+                            .timestamp_opt(not_after, 0)
+                            .unwrap()
+                            .format("%Y-%m-%d")
+                    ),
+                    self.theme.style(THEME_KEY, "text"),
+                ));
+            }
+            spans.push(Span::styled(
+                msg.get_field_string(EventFieldType::Description),
+                if state == CertState::Critical {
+                    state_style
+                } else {
+                    self.theme.style(THEME_KEY, "muted")
+                },
+            ));
+            lines.push(Line::from(spans));
+        }
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Widget, WidgetRef},
+};
+
+use crate::{
+    models::{
+        check_state::CheckState, config::ChartsConfig, event_bus_field_type::EventFieldType,
+        metric_style::MetricStyle, time_series::TimeSeries,
+    },
+    services::event_bus::EventBus,
+};
+
+use super::{controllers::checks::ChecksController, metric::MetricVisual, theme::Theme};
+
+const THEME_KEY: &str = "checks";
+
+/// One line per health check, with its latency over time
+pub struct ChecksWidget {
+    controller: ChecksController,
+    theme: Arc<Theme>,
+    latency: MetricStyle,
+}
+
+impl ChecksWidget {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, theme: Arc<Theme>, config: &ChartsConfig) -> Self {
+        Self {
+            controller: ChecksController::new(event_bus),
+            theme,
+            latency: config.checks.clone(),
+        }
+    }
+
+    /// Latencies, named after their check
+    pub fn series(&self) -> Vec<TimeSeries> {
+        self.controller
+            .checks_lock()
+            .iter()
+            .map(|check| check.latencies.clone())
+            .collect()
+    }
+}
+
+impl WidgetRef for ChecksWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Checks ").style(self.theme.style(THEME_KEY, "title")));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let checks = self.controller.checks_lock();
+        let rows = Layout::vertical(vec![Constraint::Length(1); checks.len()]).split(inner);
+
+        for (check, row) in checks.iter().zip(rows.iter()) {
+            let state =
+                CheckState::from_string(&check.last.get_field_string(EventFieldType::State));
+            let state_style = match state {
+                CheckState::Passing => self.theme.style(THEME_KEY, "ok"),
+                CheckState::Failing => self.theme.style(THEME_KEY, "error"),
+            };
+            let latency = check
+                .latencies
+                .stats()
+                .map(|stats| stats.current)
+                .unwrap_or_default();
+
+            let [text_area, spark_area] =
+                Layout::horizontal([Constraint::Percentage(70), Constraint::Fill(1)]).areas(*row);
+
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", check.last.title()),
+                    self.theme.style(THEME_KEY, "heading"),
+                ),
+                Span::styled(format!("{} ", state.to_string()), state_style),
+                Span::styled(
+                    format!("{} ", check.latencies.unit.format(latency)),
+                    self.theme.style(THEME_KEY, "text"),
+                ),
+                Span::styled(
+                    check.last.get_field_string(EventFieldType::Description),
+                    self.theme.style(THEME_KEY, "muted"),
+                ),
+            ])
+            .render(text_area, buf);
+
+            MetricVisual::new(
+                &self.latency,
+                &[(&check.latencies, state_style)],
+                &self.theme,
+                THEME_KEY,
+            )
+            .render(spark_area, buf);
+        }
+    }
+}
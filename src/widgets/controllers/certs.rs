@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::trace;
+
+use crate::{
+    models::event_bus_message::EventBusMessage,
+    services::{certs, event_bus::EventBus},
+};
+
+pub struct CertsController {
+    certs: Arc<Mutex<Vec<EventBusMessage>>>,
+}
+
+impl CertsController {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let certs = Arc::new(Mutex::new(vec![]));
+        CertsController::subscribe(event_bus, Arc::clone(&certs));
+        Self { certs }
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, certs: Arc<Mutex<Vec<EventBusMessage>>>) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(certs::EVENT_TOPIC, move |data| {
+            CertsController::on_event(data, Arc::clone(&certs));
+        });
+    }
+
+    fn on_event(data: Vec<u8>, certs: Arc<Mutex<Vec<EventBusMessage>>>) {
+        let msg = EventBusMessage::from_bytes(data);
+        trace!("CertsController: on_event: {:?}", msg);
+
+        let mut lock = certs.lock().unwrap();
+        match lock.iter().position(|elem| elem.title() == msg.title()) {
+            Some(pos) => lock[pos] = msg,
+            None => lock.push(msg),
+        }
+    }
+
+    pub fn certs_lock(&self) -> MutexGuard<'_, Vec<EventBusMessage>> {
+        self.certs.lock().unwrap()
+    }
+}
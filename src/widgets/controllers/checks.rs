@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::trace;
+
+use crate::{
+    models::{
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        time_series::{TimeSeries, Unit},
+    },
+    services::{event_bus::EventBus, health_check},
+    utils::bytes_helper::bytes_to_u64,
+};
+
+/// Seconds of latencies kept per check
+const LATENCY_WINDOW: u64 = 60 * 60;
+
+pub struct CheckResult {
+    pub last: EventBusMessage,
+    pub latencies: TimeSeries,
+}
+
+pub struct ChecksController {
+    checks: Arc<Mutex<Vec<CheckResult>>>,
+}
+
+impl ChecksController {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let checks = Arc::new(Mutex::new(vec![]));
+        ChecksController::subscribe(event_bus, Arc::clone(&checks));
+        Self { checks }
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, checks: Arc<Mutex<Vec<CheckResult>>>) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(health_check::EVENT_TOPIC, move |data| {
+            ChecksController::on_event(data, Arc::clone(&checks));
+        });
+    }
+
+    fn on_event(data: Vec<u8>, checks: Arc<Mutex<Vec<CheckResult>>>) {
+        let msg = EventBusMessage::from_bytes(data);
+        trace!("ChecksController: on_event: {:?}", msg);
+
+        let latency = bytes_to_u64(msg.get_field(EventFieldType::Latency)) as f64;
+        let ts = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+        let mut lock = checks.lock().unwrap();
+        // keep the order checks were first seen in, like the services panel
+        match lock
+            .iter_mut()
+            .find(|check| check.last.title() == msg.title())
+        {
+            Some(check) => {
+                check.latencies.push(ts, latency);
+                check.last = msg;
+            }
+            None => {
+                let mut latencies = TimeSeries::new(msg.title(), Unit::Millis, LATENCY_WINDOW);
+                latencies.push(ts, latency);
+                lock.push(CheckResult {
+                    last: msg,
+                    latencies,
+                });
+            }
+        }
+    }
+
+    pub fn checks_lock(&self) -> MutexGuard<'_, Vec<CheckResult>> {
+        self.checks.lock().unwrap()
+    }
+}
@@ -1,116 +1,594 @@
 use std::{
+    cmp::Ordering,
     collections::HashMap,
-    sync::{Arc, Mutex, MutexGuard},
-    thread::sleep,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
-use log::trace;
+use log::{info, trace};
 
 use crate::{
     models::{
-        event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage,
+        cert_state::CertState,
+        check_state::CheckState,
+        config::StatusConfig,
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
         event_type::EventType,
+        job::JobProgress,
+        maintenance::{MaintenanceMode, MaintenanceWindow},
+        plugin_state::PluginState,
+        severity::Severity,
+        status_action::StatusAction,
+        status_event::StatusTransition,
+        status_group::StatusGroup,
+        status_order::{GroupBy, SortKey},
     },
-    services::{event_bus::EventBus, process_watcher, socket},
+    services::{
+        certs, event_bus::EventBus, file_tail, health_check, heartbeat, history::StatusHistory,
+        maintenance, process_watcher, render, socket,
+    },
+    utils::bytes_helper::bytes_to_i64,
 };
 
 pub type ActiveMessages = HashMap<String, EventBusMessage>;
 pub type Messages = Arc<Mutex<ActiveMessages>>;
+pub type History = Arc<Mutex<StatusHistory>>;
+pub type Acks = Arc<Mutex<HashMap<String, Ack>>>;
 
 const DEFAULT_STATUS_TITLE: &str = "All good!";
 const DEFAULT_STATUS_DESC: &str = "Nothing happening";
 
+/// Entries that aren't reported again within this long expire, on top of the
+/// `Interval` their publisher says it waits between reports
 const CLEANUP_INTERVAL: u8 = 3;
+/// Used when a snooze doesn't say for how long
+pub const DEFAULT_SNOOZE: i64 = 15 * 60;
+
+/// What was done to an entry by hand, see `StatusAction`
+#[derive(Clone, PartialEq, Debug)]
+pub enum Ack {
+    Acknowledged,
+    SnoozedUntil(i64),
+    /// Stays hidden while it keeps being reported with the same description, so
+    /// entries that are republished every tick don't pop right back up
+    Dismissed {
+        description: String,
+        last_seen: i64,
+        expires_after: i64,
+    },
+}
+
+/// An active status with everything needed to sort and render it
+#[derive(Clone, Debug)]
+pub struct StatusEntry {
+    pub msg: EventBusMessage,
+    pub ack: Option<Ack>,
+    pub severity: Severity,
+    /// Label of the group it's shown in, empty when not grouping
+    pub group: String,
+    /// When it was first seen
+    pub since: i64,
+}
+
+/// Everything the event bus callbacks need, cloned into each of them
+#[derive(Clone)]
+struct Shared {
+    active_messages: Messages,
+    acks: Acks,
+    history: History,
+}
 
 pub struct CurrentStatusController {
-    pub active_messages: Messages,
+    shared: Shared,
+    maintenance: Arc<Mutex<Option<MaintenanceWindow>>>,
+    order: Vec<SortKey>,
+    group_by: GroupBy,
+    pin: Vec<String>,
 }
 
 impl CurrentStatusController {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
-        let active_messages = Arc::new(Mutex::new(HashMap::new()));
-        CurrentStatusController::cleanup_task(Arc::clone(&active_messages));
-        CurrentStatusController::subscribe(Arc::clone(&event_bus), Arc::clone(&active_messages));
-        Self { active_messages }
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        history: History,
+        config: &StatusConfig,
+        reporters: &[String],
+    ) -> Self {
+        // bring back socket jobs that were still running when we last exited
+        let active_messages: ActiveMessages = history
+            .lock()
+            .unwrap()
+            .open_statuses()
+            .into_iter()
+            .map(|(title, description)| {
+                let msg = EventBusMessage::new(
+                    &title,
+                    EventType::Socket,
+                    Some(vec![(
+                        EventFieldType::Description,
+                        description.into_bytes(),
+                    )]),
+                );
+                (title, msg)
+            })
+            .collect();
+
+        let shared = Shared {
+            active_messages: Arc::new(Mutex::new(active_messages)),
+            acks: Arc::new(Mutex::new(HashMap::new())),
+            history,
+        };
+        let maintenance = Arc::new(Mutex::new(None));
+        CurrentStatusController::cleanup_task(Arc::clone(&event_bus), shared.clone());
+        CurrentStatusController::subscribe(Arc::clone(&event_bus), shared.clone());
+        CurrentStatusController::subscribe_reporters(
+            Arc::clone(&event_bus),
+            shared.clone(),
+            reporters,
+        );
+        CurrentStatusController::subscribe_maintenance(
+            Arc::clone(&event_bus),
+            Arc::clone(&maintenance),
+        );
+        Self {
+            shared,
+            maintenance,
+            order: config.order.clone(),
+            group_by: config.group_by.clone(),
+            pin: config.pin.clone(),
+        }
     }
 
-    fn subscribe(event_bus: Arc<Mutex<EventBus>>, active_messages: Messages) {
+    fn subscribe_maintenance(
+        event_bus: Arc<Mutex<EventBus>>,
+        maintenance: Arc<Mutex<Option<MaintenanceWindow>>>,
+    ) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(maintenance::EVENT_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            // an inactive window is published without any fields
+            let window = msg
+                .has_field(EventFieldType::Timestamp)
+                .then(|| MaintenanceWindow {
+                    until: bytes_to_i64(msg.get_field(EventFieldType::Timestamp)),
+                    reason: msg.get_field_string(EventFieldType::Description),
+                    mode: MaintenanceMode::from_string(
+                        &msg.get_field_string(EventFieldType::State),
+                    ),
+                });
+            *maintenance.lock().unwrap() = window;
+        });
+    }
+
+    /// Statuses from plugins and scripts show up like alerts, metrics on the same
+    /// topics are left to the plugins panel
+    fn subscribe_reporters(event_bus: Arc<Mutex<EventBus>>, shared: Shared, topics: &[String]) {
+        let mut lock = event_bus.lock().unwrap();
+        for topic in topics {
+            let state = shared.clone();
+            lock.subscribe(topic, move |data| {
+                let msg = EventBusMessage::from_bytes(data);
+                if msg.has_field(EventFieldType::Value) {
+                    return;
+                }
+                let alerting =
+                    PluginState::from_string(&msg.get_field_string(EventFieldType::State))
+                        != PluginState::Ok;
+                CurrentStatusController::on_alert(&state, msg, alerting);
+            });
+        }
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, shared: Shared) {
         let mut lock = event_bus.lock().unwrap();
-        let active_messages_1 = Arc::clone(&active_messages);
-        let active_messages_2 = Arc::clone(&active_messages);
 
         // watch processes
+        let state = shared.clone();
         lock.subscribe(process_watcher::EVENT_TOPIC, move |data| {
-            CurrentStatusController::on_event(Arc::clone(&active_messages_1), data);
+            CurrentStatusController::on_event(&state, EventBusMessage::from_bytes(data));
         });
 
         // watch messages on socket
+        let state = shared.clone();
         lock.subscribe(socket::EVENT_TOPIC, move |data| {
-            CurrentStatusController::on_event(Arc::clone(&active_messages_2), data);
+            CurrentStatusController::on_event(&state, EventBusMessage::from_bytes(data));
+        });
+
+        // missed heartbeats are republished every tick, so they expire like processes
+        let state = shared.clone();
+        lock.subscribe(heartbeat::EVENT_TOPIC, move |data| {
+            CurrentStatusController::on_event(&state, EventBusMessage::from_bytes(data));
+        });
+
+        // only failing checks show up, until they pass again
+        let state = shared.clone();
+        lock.subscribe(health_check::EVENT_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            let alerting = CheckState::from_string(&msg.get_field_string(EventFieldType::State))
+                == CheckState::Failing;
+            CurrentStatusController::on_alert(&state, msg, alerting);
+        });
+
+        // certificates show up once they get close to expiring
+        let state = shared.clone();
+        lock.subscribe(certs::EVENT_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            let alerting = CertState::from_string(&msg.get_field_string(EventFieldType::State))
+                != CertState::Valid;
+            CurrentStatusController::on_alert(&state, msg, alerting);
+        });
+
+        // log triggers stay up until a clearing line shows up
+        let state = shared.clone();
+        lock.subscribe(file_tail::TRIGGER_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            let alerting = msg.get_field_string(EventFieldType::State) == file_tail::TRIGGER_RAISED;
+            CurrentStatusController::on_alert(&state, msg, alerting);
+        });
+
+        // acknowledge, dismiss and snooze from scripts
+        let state = shared;
+        lock.subscribe(socket::STATUS_REQUEST_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            let action = StatusAction::from_string(&msg.get_field_string(EventFieldType::State));
+            let until = msg
+                .has_field(EventFieldType::Timestamp)
+                .then(|| bytes_to_i64(msg.get_field(EventFieldType::Timestamp)));
+            CurrentStatusController::apply(&state, msg.title(), &action, until);
         });
     }
 
-    fn cleanup_task(active_messages: Messages) {
+    fn cleanup_task(event_bus: Arc<Mutex<EventBus>>, shared: Shared) {
         tokio::spawn(async move {
             loop {
-                let mut lock = active_messages.lock().unwrap();
-                let now = chrono::Utc::now().timestamp();
-                (*lock).clone().into_iter().for_each(|(key, msg)| {
-                    // don't delete socket messages, instead wait for a explicit message
-                    // we also don't want to remove the default status message
-                    if key == DEFAULT_STATUS_TITLE || *msg.event_type() == EventType::Socket {
-                        return;
-                    }
-
-                    if now - msg.ts() >= CLEANUP_INTERVAL as i64 {
-                        (*lock).remove(&key);
-                    }
-                });
-                if lock.is_empty() {
-                    lock.insert(
-                        DEFAULT_STATUS_TITLE.to_string(),
-                        EventBusMessage::new(
-                            DEFAULT_STATUS_TITLE,
-                            EventType::Process,
-                            Some(vec![(
-                                EventFieldType::Description,
-                                DEFAULT_STATUS_DESC.as_bytes().to_vec(),
-                            )]),
-                        ),
-                    );
+                if CurrentStatusController::cleanup(&shared) {
+                    render::mark_dirty(&event_bus.lock().unwrap());
                 }
-                drop(lock);
-                sleep(Duration::from_millis(100));
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+    }
+
+    /// Seconds without a report before an entry goes away. Sources polling on a
+    /// slow or idle interval say so, or they'd flap between reports
+    fn expires_after(msg: &EventBusMessage) -> i64 {
+        let interval = match msg.has_field(EventFieldType::Interval) {
+            true => bytes_to_i64(msg.get_field(EventFieldType::Interval)).max(0),
+            false => 0,
+        };
+        interval.saturating_add(CLEANUP_INTERVAL as i64)
+    }
+
+    /// Returns true if anything expired
+    fn cleanup(shared: &Shared) -> bool {
+        let mut lock = shared.active_messages.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let before = lock.len();
+        (*lock).clone().into_iter().for_each(|(key, msg)| {
+            // don't delete socket messages or alerts, instead wait for a explicit
+            // message. we also don't want to remove the default status message
+            if key == DEFAULT_STATUS_TITLE
+                || matches!(
+                    msg.event_type(),
+                    EventType::Socket
+                        | EventType::HealthCheck
+                        | EventType::Certificate
+                        | EventType::LogLine
+                        | EventType::Plugin
+                        | EventType::Script
+                )
+            {
+                return;
+            }
+
+            if now - msg.ts() >= CurrentStatusController::expires_after(&msg) {
+                (*lock).remove(&key);
+                shared
+                    .history
+                    .lock()
+                    .unwrap()
+                    .closed(&msg, StatusTransition::Expired);
             }
         });
+
+        // acks only live as long as their entry, snoozes until they run out
+        let mut acks = shared.acks.lock().unwrap();
+        let acks_before = acks.len();
+        acks.retain(|title, ack| match ack {
+            Ack::Acknowledged => lock.contains_key(title),
+            Ack::SnoozedUntil(until) => *until > now,
+            Ack::Dismissed {
+                last_seen,
+                expires_after,
+                ..
+            } => now - *last_seen < *expires_after,
+        });
+        let mut changed = lock.len() != before || acks.len() != acks_before;
+
+        if lock.is_empty() {
+            changed = true;
+            lock.insert(
+                DEFAULT_STATUS_TITLE.to_string(),
+                EventBusMessage::new(
+                    DEFAULT_STATUS_TITLE,
+                    EventType::Process,
+                    Some(vec![(
+                        EventFieldType::Description,
+                        DEFAULT_STATUS_DESC.as_bytes().to_vec(),
+                    )]),
+                ),
+            );
+        }
+        changed
     }
 
-    fn on_event(active_messages: Messages, data: Vec<u8>) {
-        let msg = EventBusMessage::from_bytes(data);
+    fn on_event(shared: &Shared, msg: EventBusMessage) {
         trace!("CurrentStatusController: on_event: {:?}", msg);
 
-        let mut lock = active_messages.lock().unwrap();
+        let mut lock = shared.active_messages.lock().unwrap();
+        let description = msg.get_field_string(EventFieldType::Description);
+
+        {
+            let mut acks = shared.acks.lock().unwrap();
+            if let Some(Ack::Dismissed {
+                description: dismissed,
+                last_seen,
+                expires_after,
+            }) = acks.get_mut(msg.title())
+            {
+                if *dismissed == description {
+                    *last_seen = msg.ts();
+                    *expires_after = CurrentStatusController::expires_after(&msg);
+                    return;
+                }
+                acks.remove(msg.title());
+            }
+        }
+
         if lock.len() == 1 && lock.get(DEFAULT_STATUS_TITLE).is_some() {
             lock.remove(DEFAULT_STATUS_TITLE);
         }
 
+        let description = description.to_lowercase();
+
         // remove if the message says it's SOCKET_DONE_TEXT and it's a socket
         if *msg.event_type() == EventType::Socket
-            && msg
-                .get_field_string(EventFieldType::Description)
-                .to_lowercase()
-                == socket::SOCKET_DONE_TEXT
+            && description == socket::SOCKET_DONE_TEXT
             && lock.contains_key(msg.title())
         {
             lock.remove(msg.title());
+            shared
+                .history
+                .lock()
+                .unwrap()
+                .closed(&msg, StatusTransition::Done);
             return;
         }
 
+        // failed jobs stay visible, but stop counting towards their duration
+        if *msg.event_type() == EventType::Socket
+            && description.starts_with(socket::SOCKET_FAILED_TEXT)
+        {
+            let mut history = shared.history.lock().unwrap();
+            if history.job(msg.title()).is_some() {
+                history.closed(&msg, StatusTransition::Failed);
+            }
+            lock.insert(msg.title().to_string(), msg);
+            return;
+        }
+
+        shared.history.lock().unwrap().seen(&msg);
         lock.insert(msg.title().to_string(), msg);
     }
 
-    pub fn get_message_lock(&self) -> MutexGuard<ActiveMessages> {
-        self.active_messages.lock().unwrap()
+    /// For sources that report both good and bad results, only the bad ones show
+    /// up and stay until a good result for the same title comes in
+    fn on_alert(shared: &Shared, msg: EventBusMessage, alerting: bool) {
+        if alerting {
+            CurrentStatusController::on_event(shared, msg);
+            return;
+        }
+
+        let mut lock = shared.active_messages.lock().unwrap();
+        if lock
+            .get(msg.title())
+            .is_some_and(|active| active.event_type() == msg.event_type())
+        {
+            lock.remove(msg.title());
+            shared
+                .history
+                .lock()
+                .unwrap()
+                .closed(&msg, StatusTransition::Done);
+        }
+    }
+
+    /// Returns false if there's no such entry. `until` is only used for snoozing
+    fn apply(shared: &Shared, title: &str, action: &StatusAction, until: Option<i64>) -> bool {
+        let mut lock = shared.active_messages.lock().unwrap();
+        let Some(msg) = lock.get(title).cloned() else {
+            return false;
+        };
+        if title == DEFAULT_STATUS_TITLE {
+            return false;
+        }
+        info!("{} status '{title}'", action.to_string());
+
+        let mut acks = shared.acks.lock().unwrap();
+        let mut history = shared.history.lock().unwrap();
+        match action {
+            StatusAction::Acknowledge => {
+                acks.insert(title.to_string(), Ack::Acknowledged);
+                history.noted(&msg, StatusTransition::Acknowledged);
+            }
+            StatusAction::Dismiss => {
+                lock.remove(title);
+                acks.insert(
+                    title.to_string(),
+                    Ack::Dismissed {
+                        description: msg.get_field_string(EventFieldType::Description),
+                        last_seen: chrono::Utc::now().timestamp(),
+                        expires_after: CurrentStatusController::expires_after(&msg),
+                    },
+                );
+                history.closed(&msg, StatusTransition::Dismissed);
+            }
+            StatusAction::Snooze => {
+                let until = until.unwrap_or(chrono::Utc::now().timestamp() + DEFAULT_SNOOZE);
+                acks.insert(title.to_string(), Ack::SnoozedUntil(until));
+                history.noted(&msg, StatusTransition::Snoozed);
+            }
+        }
+        true
+    }
+
+    pub fn acknowledge(&self, title: &str) -> bool {
+        CurrentStatusController::apply(&self.shared, title, &StatusAction::Acknowledge, None)
+    }
+
+    pub fn dismiss(&self, title: &str) -> bool {
+        CurrentStatusController::apply(&self.shared, title, &StatusAction::Dismiss, None)
+    }
+
+    pub fn snooze(&self, title: &str, secs: i64) -> bool {
+        // a count too large to be a date just means for good
+        let until = chrono::Utc::now().timestamp().saturating_add(secs);
+        CurrentStatusController::apply(&self.shared, title, &StatusAction::Snooze, Some(until))
+    }
+
+    /// Entries to show without snoozed ones, and how many were snoozed. Pinned
+    /// titles come first, then groups in the order of their first entry, each
+    /// sorted by `primary` and the configured order
+    pub fn get_entries(&self, primary: Option<&SortKey>) -> (Vec<StatusEntry>, usize) {
+        let lock = self.shared.active_messages.lock().unwrap();
+        let acks = self.shared.acks.lock().unwrap();
+        let history = self.shared.history.lock().unwrap();
+
+        let mut entries = lock
+            .values()
+            .map(|msg| StatusEntry {
+                msg: msg.clone(),
+                ack: acks.get(msg.title()).cloned(),
+                severity: CurrentStatusController::severity(msg, history.job(msg.title())),
+                group: self.group(msg),
+                since: history.since(msg.title()).unwrap_or(msg.ts()),
+            })
+            .collect::<Vec<StatusEntry>>();
+        let total = entries.len();
+        entries.retain(|entry| !matches!(entry.ack, Some(Ack::SnoozedUntil(_))));
+
+        let keys = primary
+            .into_iter()
+            .chain(self.order.iter().filter(|key| Some(*key) != primary))
+            .collect::<Vec<&SortKey>>();
+        entries.sort_by(|a, b| {
+            keys.iter()
+                .fold(Ordering::Equal, |ord, key| {
+                    ord.then_with(|| CurrentStatusController::compare(key, a, b))
+                })
+                .then_with(|| a.msg.title().cmp(b.msg.title()))
+        });
+
+        // stable, so entries keep their order within a group
+        let mut groups: Vec<String> = vec![];
+        for entry in &entries {
+            if !groups.contains(&entry.group) {
+                groups.push(entry.group.clone());
+            }
+        }
+        entries.sort_by_key(|entry| {
+            (
+                self.pin_position(entry.msg.title()).unwrap_or(usize::MAX),
+                groups.iter().position(|group| *group == entry.group),
+            )
+        });
+
+        let snoozed = total - entries.len();
+        (entries, snoozed)
+    }
+
+    /// The configured sort key first, used by the status table
+    pub fn primary_sort(&self) -> Option<&SortKey> {
+        self.order.first()
+    }
+
+    fn pin_position(&self, title: &str) -> Option<usize> {
+        self.pin.iter().position(|pinned| pinned == title)
+    }
+
+    fn group(&self, msg: &EventBusMessage) -> String {
+        if self.pin_position(msg.title()).is_some() {
+            return "pinned".to_string();
+        }
+        match self.group_by {
+            GroupBy::Type => msg.event_type().name().to_string(),
+            GroupBy::Source => StatusGroup::from_event_type(msg.event_type())
+                .to_string()
+                .to_string(),
+            GroupBy::None => String::new(),
+        }
+    }
+
+    fn compare(key: &SortKey, a: &StatusEntry, b: &StatusEntry) -> Ordering {
+        match key {
+            SortKey::Severity => a.severity.cmp(&b.severity),
+            SortKey::Since => a.since.cmp(&b.since),
+            SortKey::Title => a.msg.title().cmp(b.msg.title()),
+        }
+    }
+
+    /// Missed heartbeats, failing checks, log triggers and failed jobs are always
+    /// bad news, certificates and overdue jobs only get there eventually. Plugins
+    /// and scripts say for themselves
+    fn severity(msg: &EventBusMessage, job: Option<JobProgress>) -> Severity {
+        match msg.event_type() {
+            EventType::Heartbeat | EventType::HealthCheck | EventType::LogLine => {
+                Severity::Critical
+            }
+            EventType::Plugin | EventType::Script => {
+                match PluginState::from_string(&msg.get_field_string(EventFieldType::State)) {
+                    PluginState::Warning => Severity::Warning,
+                    _ => Severity::Critical,
+                }
+            }
+            EventType::Certificate => {
+                match CertState::from_string(&msg.get_field_string(EventFieldType::State)) {
+                    CertState::Expiring => Severity::Warning,
+                    _ => Severity::Critical,
+                }
+            }
+            EventType::Socket
+                if msg
+                    .get_field_string(EventFieldType::Description)
+                    .to_lowercase()
+                    .starts_with(socket::SOCKET_FAILED_TEXT) =>
+            {
+                Severity::Critical
+            }
+            _ if job.is_some_and(|job| job.overdue) => Severity::Warning,
+            _ => Severity::Info,
+        }
+    }
+
+    /// When an active entry was last reported, whether it's snoozed or not
+    pub fn last_seen(&self, title: &str) -> Option<i64> {
+        let lock = self.shared.active_messages.lock().unwrap();
+        lock.get(title).map(|msg| msg.ts())
+    }
+
+    /// Whether `title` can be acknowledged, dismissed or snoozed
+    pub fn is_actionable(title: &str) -> bool {
+        title != DEFAULT_STATUS_TITLE
+    }
+
+    /// Timing of a socket job, `None` for processes
+    pub fn get_job(&self, title: &str) -> Option<JobProgress> {
+        self.shared.history.lock().unwrap().job(title)
+    }
+
+    /// Messages are still tracked during maintenance, it's up to the widget to
+    /// hide or grey them out depending on the mode
+    pub fn get_maintenance(&self) -> Option<MaintenanceWindow> {
+        self.maintenance.lock().unwrap().clone()
     }
 }
@@ -1,43 +1,133 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    io::{self, ErrorKind},
+    sync::{Arc, Mutex},
+};
 
-use chrono::{TimeZone, Utc};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, Local, NaiveDate, TimeZone, Utc,
+};
+use chrono_tz::Tz;
 use log::trace;
 
 use crate::{
-    models::{event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage},
+    models::{
+        config::ClockConfig, event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+    },
     services::{datetime, event_bus::EventBus},
-    utils::bytes_helper::bytes_to_i64,
+    utils::{
+        bytes_helper::{bytes_to_i64, bytes_to_u64},
+        duration::format_duration,
+    },
 };
 
 pub struct DateTimeController {
     timestamp: Arc<Mutex<i64>>,
+    uptime: Arc<Mutex<u64>>,
+    format: String,
+    date_format: String,
+    timezones: Vec<Tz>,
+    pub calendar: bool,
 }
 
 impl DateTimeController {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, config: &ClockConfig) -> io::Result<Self> {
+        for format in [&config.format, &config.date_format] {
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid clock format '{format}'"),
+                ));
+            }
+        }
+
+        let timezones = config
+            .timezones
+            .iter()
+            .map(|name| {
+                name.parse::<Tz>().map_err(|_| {
+                    io::Error::new(ErrorKind::InvalidData, format!("unknown timezone '{name}'"))
+                })
+            })
+            .collect::<io::Result<Vec<Tz>>>()?;
+
         let timestamp = Arc::new(Mutex::new(chrono::Utc::now().timestamp()));
-        DateTimeController::subscribe(Arc::clone(&event_bus), Arc::clone(&timestamp));
-        Self { timestamp }
+        let uptime = Arc::new(Mutex::new(0));
+        DateTimeController::subscribe(
+            Arc::clone(&event_bus),
+            Arc::clone(&timestamp),
+            Arc::clone(&uptime),
+        );
+        Ok(Self {
+            timestamp,
+            uptime,
+            format: config.format.clone(),
+            date_format: config.date_format.clone(),
+            timezones,
+            calendar: config.calendar,
+        })
     }
 
-    fn subscribe(event_bus: Arc<Mutex<EventBus>>, timestamp: Arc<Mutex<i64>>) {
+    fn subscribe(
+        event_bus: Arc<Mutex<EventBus>>,
+        timestamp: Arc<Mutex<i64>>,
+        uptime: Arc<Mutex<u64>>,
+    ) {
         let mut lock = event_bus.lock().unwrap();
         lock.subscribe(datetime::EVENT_TOPIC, move |data| {
-            DateTimeController::on_event(data, Arc::clone(&timestamp));
+            DateTimeController::on_event(data, Arc::clone(&timestamp), Arc::clone(&uptime));
         });
     }
 
-    fn on_event(data: Vec<u8>, timestamp: Arc<Mutex<i64>>) {
+    fn on_event(data: Vec<u8>, timestamp: Arc<Mutex<i64>>, uptime: Arc<Mutex<u64>>) {
         let msg = EventBusMessage::from_bytes(data);
         trace!("DateTimeController: on_event: {:?}", msg);
 
         let ts = bytes_to_i64(msg.get_field(EventFieldType::Timestamp));
         (*timestamp.lock().unwrap()) = ts;
+        (*uptime.lock().unwrap()) = bytes_to_u64(msg.get_field(EventFieldType::Uptime));
     }
 
-    pub fn get_formatted_time(&self) -> String {
+    fn now(&self) -> DateTime<Utc> {
         let ts = self.timestamp.lock().unwrap();
-        let datetime = Utc.timestamp_opt(*ts, 0);
-        datetime.unwrap().format("%H:%M:%S").to_string()
+        Utc.timestamp_opt(*ts, 0).unwrap()
+    }
+
+    pub fn get_formatted_time(&self) -> String {
+        self.now()
+            .with_timezone(&Local)
+            .format(&self.format)
+            .to_string()
+    }
+
+    pub fn get_formatted_date(&self) -> String {
+        let local = self.now().with_timezone(&Local);
+        format!(
+            "{}, week {}",
+            local.format(&self.date_format),
+            local.iso_week().week()
+        )
+    }
+
+    pub fn get_today(&self) -> NaiveDate {
+        self.now().with_timezone(&Local).date_naive()
+    }
+
+    /// Returns the city part of each configured timezone along with its time
+    pub fn get_zone_times(&self) -> Vec<(String, String)> {
+        let now = self.now();
+        self.timezones
+            .iter()
+            .map(|tz| {
+                let name = tz.name();
+                let city = name.rsplit('/').next().unwrap_or(name).replace('_', " ");
+                (city, now.with_timezone(tz).format(&self.format).to_string())
+            })
+            .collect()
+    }
+
+    pub fn get_uptime(&self) -> String {
+        format_duration(*self.uptime.lock().unwrap())
     }
 }
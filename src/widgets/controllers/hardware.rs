@@ -1,73 +1,79 @@
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Mutex};
 
 use log::trace;
 
 use crate::{
-    models::{event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage},
+    models::{
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        time_series::{TimeSeries, Unit},
+    },
     services::{event_bus::EventBus, hw_usage},
     utils::bytes_helper::bytes_to_f64,
 };
 
+/// How far back the charts go, in seconds
+const WINDOW: u64 = 300;
+
+#[derive(Clone)]
+pub struct HardwareSeries {
+    pub cpu: TimeSeries,
+    pub memory: TimeSeries,
+    pub load: TimeSeries,
+    pub rx: TimeSeries,
+    pub tx: TimeSeries,
+}
+
 pub struct HardwareUsageController {
-    pub ram: Arc<Mutex<Vec<f64>>>,
-    pub cpu: Arc<Mutex<Vec<f64>>>,
-    pub history: f64,
+    series: Arc<Mutex<HardwareSeries>>,
 }
 
 impl HardwareUsageController {
     pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
-        let ram = Arc::new(Mutex::new(vec![]));
-        let cpu = Arc::new(Mutex::new(vec![]));
-        let history = 100.0;
+        let series = Arc::new(Mutex::new(HardwareSeries {
+            cpu: TimeSeries::new("CPU", Unit::Percent, WINDOW),
+            memory: TimeSeries::new("Memory", Unit::Percent, WINDOW),
+            load: TimeSeries::new("Load", Unit::Plain, WINDOW),
+            rx: TimeSeries::new("Received", Unit::BytesPerSec, WINDOW),
+            tx: TimeSeries::new("Sent", Unit::BytesPerSec, WINDOW),
+        }));
 
-        HardwareUsageController::subscribe(event_bus, history, Arc::clone(&cpu), Arc::clone(&ram));
+        HardwareUsageController::subscribe(event_bus, Arc::clone(&series));
 
-        Self { ram, cpu, history }
+        Self { series }
     }
 
-    fn subscribe(
-        event_bus: Arc<Mutex<EventBus>>,
-        limit: f64,
-        cpu: Arc<Mutex<Vec<f64>>>,
-        ram: Arc<Mutex<Vec<f64>>>,
-    ) {
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, series: Arc<Mutex<HardwareSeries>>) {
         let mut lock = event_bus.lock().unwrap();
 
         // watch hw usage
         lock.subscribe(hw_usage::EVENT_TOPIC, move |data| {
-            HardwareUsageController::on_event(data, limit, Arc::clone(&cpu), Arc::clone(&ram));
+            HardwareUsageController::on_event(data, &series);
         });
     }
 
-    fn on_event(data: Vec<u8>, limit: f64, cpu: Arc<Mutex<Vec<f64>>>, ram: Arc<Mutex<Vec<f64>>>) {
+    fn on_event(data: Vec<u8>, series: &Mutex<HardwareSeries>) {
         let msg = EventBusMessage::from_bytes(data);
-        let cpu_usage = bytes_to_f64(msg.get_field(EventFieldType::Cpu));
-        let ram_usage = bytes_to_f64(msg.get_field(EventFieldType::Memory));
-
-        let mut cpu_lock = cpu.lock().unwrap();
-        let mut ram_lock = ram.lock().unwrap();
-        cpu_lock.push(cpu_usage);
-        ram_lock.push(ram_usage);
+        // the message timestamp only has whole seconds
+        let ts = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
 
-        trace!("adding cpu: {}", cpu_lock.len());
-        trace!("adding ram: {}", ram_lock.len());
-
-        if ram_lock.len() > limit as usize {
-            cpu_lock.remove(0);
-            ram_lock.remove(0);
-            trace!("removing");
+        let mut lock = series.lock().unwrap();
+        let lock = &mut *lock;
+        for (series, field) in [
+            (&mut lock.cpu, EventFieldType::Cpu),
+            (&mut lock.memory, EventFieldType::Memory),
+            (&mut lock.load, EventFieldType::Load),
+            (&mut lock.rx, EventFieldType::NetworkRx),
+            (&mut lock.tx, EventFieldType::NetworkTx),
+        ] {
+            if msg.has_field(field.clone()) {
+                series.push(ts, bytes_to_f64(msg.get_field(field)));
+            }
         }
+        trace!("hardware samples at {ts}");
     }
 
-    pub fn cpu_lock(&self) -> MutexGuard<'_, Vec<f64>> {
-        let lock = self.cpu.lock().unwrap();
-        trace!("cpu lock len: {}", lock.len());
-        lock
-    }
-
-    pub fn ram_lock(&self) -> MutexGuard<'_, Vec<f64>> {
-        let lock = self.ram.lock().unwrap();
-        trace!("ram lock len: {}", lock.len());
-        lock
+    pub fn get_series(&self) -> HardwareSeries {
+        self.series.lock().unwrap().clone()
     }
 }
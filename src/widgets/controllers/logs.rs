@@ -0,0 +1,44 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    models::{event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage},
+    services::{event_bus::EventBus, file_tail},
+};
+
+/// File name and line
+pub type LogLines = VecDeque<(String, String)>;
+
+pub struct LogsController {
+    lines: Arc<Mutex<LogLines>>,
+}
+
+impl LogsController {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, max_lines: usize) -> Self {
+        let lines = Arc::new(Mutex::new(VecDeque::new()));
+        LogsController::subscribe(event_bus, Arc::clone(&lines), max_lines);
+        Self { lines }
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, lines: Arc<Mutex<LogLines>>, max_lines: usize) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(file_tail::EVENT_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+
+            let mut lock = lines.lock().unwrap();
+            lock.push_back((
+                msg.title().to_string(),
+                msg.get_field_string(EventFieldType::Description),
+            ));
+            while lock.len() > max_lines {
+                lock.pop_front();
+            }
+        });
+    }
+
+    pub fn lines_lock(&self) -> MutexGuard<'_, LogLines> {
+        self.lines.lock().unwrap()
+    }
+}
@@ -1,3 +1,10 @@
+pub mod certs;
+pub mod checks;
 pub mod current_status;
 pub mod datetime;
 pub mod hardware;
+pub mod logs;
+pub mod plugins;
+pub mod scripts;
+pub mod services;
+pub mod timeline;
@@ -0,0 +1,75 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::trace;
+
+use crate::{
+    models::{
+        event_bus_field_type::EventFieldType,
+        event_bus_message::EventBusMessage,
+        time_series::{TimeSeries, Unit},
+    },
+    services::event_bus::EventBus,
+    utils::bytes_helper::bytes_to_f64,
+};
+
+/// Seconds of values kept per metric
+const METRIC_WINDOW: u64 = 60 * 60;
+
+pub struct PluginsController {
+    metrics: Arc<Mutex<Vec<TimeSeries>>>,
+}
+
+impl PluginsController {
+    /// `topics` of plugins and scripts, they report metrics the same way
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, topics: &[String]) -> Self {
+        let metrics = Arc::new(Mutex::new(vec![]));
+        PluginsController::subscribe(event_bus, Arc::clone(&metrics), topics);
+        Self { metrics }
+    }
+
+    fn subscribe(
+        event_bus: Arc<Mutex<EventBus>>,
+        metrics: Arc<Mutex<Vec<TimeSeries>>>,
+        topics: &[String],
+    ) {
+        let mut lock = event_bus.lock().unwrap();
+        for topic in topics {
+            let metrics = Arc::clone(&metrics);
+            lock.subscribe(topic, move |data| {
+                PluginsController::on_event(data, Arc::clone(&metrics));
+            });
+        }
+    }
+
+    fn on_event(data: Vec<u8>, metrics: Arc<Mutex<Vec<TimeSeries>>>) {
+        let msg = EventBusMessage::from_bytes(data);
+        // statuses go to the status panel
+        if !msg.has_field(EventFieldType::Value) {
+            return;
+        }
+        trace!("PluginsController: on_event: {:?}", msg);
+
+        let value = bytes_to_f64(msg.get_field(EventFieldType::Value));
+        let unit = Unit::from_string(&msg.get_field_string(EventFieldType::Unit));
+        let ts = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+        let mut lock = metrics.lock().unwrap();
+        // keep the order metrics were first seen in, like the checks panel
+        match lock.iter_mut().find(|series| series.name == msg.title()) {
+            // a plugin changing the unit starts over, the old values mean something else
+            Some(series) if series.unit != unit => {
+                *series = TimeSeries::new(msg.title(), unit, METRIC_WINDOW);
+                series.push(ts, value);
+            }
+            Some(series) => series.push(ts, value),
+            None => {
+                let mut series = TimeSeries::new(msg.title(), unit, METRIC_WINDOW);
+                series.push(ts, value);
+                lock.push(series);
+            }
+        }
+    }
+
+    pub fn metrics_lock(&self) -> MutexGuard<'_, Vec<TimeSeries>> {
+        self.metrics.lock().unwrap()
+    }
+}
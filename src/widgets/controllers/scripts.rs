@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::trace;
+
+use crate::{
+    models::event_bus_message::EventBusMessage,
+    services::{event_bus::EventBus, scripts},
+};
+
+pub struct ScriptsController {
+    displays: Arc<Mutex<Vec<EventBusMessage>>>,
+}
+
+impl ScriptsController {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let displays = Arc::new(Mutex::new(vec![]));
+        ScriptsController::subscribe(event_bus, Arc::clone(&displays));
+        Self { displays }
+    }
+
+    fn subscribe(event_bus: Arc<Mutex<EventBus>>, displays: Arc<Mutex<Vec<EventBusMessage>>>) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(scripts::DISPLAY_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            trace!("ScriptsController: on_event: {:?}", msg);
+            let mut lock = displays.lock().unwrap();
+            // keep the order scripts first showed something in
+            match lock
+                .iter_mut()
+                .find(|display| display.title() == msg.title())
+            {
+                Some(display) => *display = msg,
+                None => lock.push(msg),
+            }
+        });
+    }
+
+    /// The last thing each script showed
+    pub fn displays_lock(&self) -> MutexGuard<'_, Vec<EventBusMessage>> {
+        self.displays.lock().unwrap()
+    }
+}
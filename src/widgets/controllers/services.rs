@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::trace;
+
+use crate::{
+    models::{event_bus_field_type::EventFieldType, event_bus_message::EventBusMessage},
+    services::{event_bus::EventBus, hw_usage, supervisor},
+    utils::bytes_helper::bytes_to_f64,
+};
+
+/// What server-tui itself uses, CPU in percent of a core and memory in bytes
+#[derive(Clone, Copy)]
+pub struct SelfUsage {
+    pub cpu: f64,
+    pub memory: f64,
+}
+
+pub struct ServicesController {
+    services: Arc<Mutex<Vec<EventBusMessage>>>,
+    self_usage: Arc<Mutex<Option<SelfUsage>>>,
+}
+
+impl ServicesController {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+        let services = Arc::new(Mutex::new(vec![]));
+        let self_usage = Arc::new(Mutex::new(None));
+        ServicesController::subscribe(event_bus, Arc::clone(&services), Arc::clone(&self_usage));
+        Self {
+            services,
+            self_usage,
+        }
+    }
+
+    fn subscribe(
+        event_bus: Arc<Mutex<EventBus>>,
+        services: Arc<Mutex<Vec<EventBusMessage>>>,
+        self_usage: Arc<Mutex<Option<SelfUsage>>>,
+    ) {
+        let mut lock = event_bus.lock().unwrap();
+        lock.subscribe(supervisor::EVENT_TOPIC, move |data| {
+            ServicesController::on_event(data, Arc::clone(&services));
+        });
+
+        lock.subscribe(hw_usage::SELF_TOPIC, move |data| {
+            let msg = EventBusMessage::from_bytes(data);
+            *self_usage.lock().unwrap() = Some(SelfUsage {
+                cpu: bytes_to_f64(msg.get_field(EventFieldType::Cpu)),
+                memory: bytes_to_f64(msg.get_field(EventFieldType::Memory)),
+            });
+        });
+    }
+
+    fn on_event(data: Vec<u8>, services: Arc<Mutex<Vec<EventBusMessage>>>) {
+        let msg = EventBusMessage::from_bytes(data);
+        trace!("ServicesController: on_event: {:?}", msg);
+
+        // keep the order services were first seen in, so the panel doesn't jump around
+        let mut lock = services.lock().unwrap();
+        match lock.iter().position(|elem| elem.title() == msg.title()) {
+            Some(pos) => lock[pos] = msg,
+            None => lock.push(msg),
+        }
+    }
+
+    pub fn get_self_usage(&self) -> Option<SelfUsage> {
+        *self.self_usage.lock().unwrap()
+    }
+
+    pub fn services_lock(&self) -> MutexGuard<'_, Vec<EventBusMessage>> {
+        self.services.lock().unwrap()
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{models::status_event::StatusEvent, services::history::StatusHistory};
+
+pub struct TimelineController {
+    history: Arc<Mutex<StatusHistory>>,
+}
+
+impl TimelineController {
+    pub fn new(history: Arc<Mutex<StatusHistory>>) -> Self {
+        Self { history }
+    }
+
+    /// Newest first, `title` matches case insensitive substrings
+    pub fn get_events(&self, title: &str, source: Option<&str>) -> Vec<StatusEvent> {
+        let title = title.to_lowercase();
+        self.history
+            .lock()
+            .unwrap()
+            .events()
+            .iter()
+            .rev()
+            .filter(|event| event.title.to_lowercase().contains(&title))
+            .filter(|event| source.is_none_or(|source| event.source == source))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,67 +1,459 @@
+use std::cell::Cell;
 use std::sync::{Arc, Mutex};
 
-use ratatui::layout::{Alignment, Constraint, Direction, Flex};
+use chrono::{Local, TimeZone};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Constraint, Flex, Layout};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::Line;
-use ratatui::widgets::Paragraph;
+use ratatui::widgets::{Paragraph, Row, Table};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::Stylize,
     widgets::{Block, Widget, WidgetRef},
 };
 
+use crate::models::config::StatusConfig;
 use crate::models::event_bus_field_type::EventFieldType;
-use crate::services::event_bus::EventBus;
-use crate::utils;
+use crate::models::job::JobProgress;
+use crate::models::maintenance::{MaintenanceMode, MaintenanceWindow};
+use crate::models::severity::Severity;
+use crate::models::snapshot::StatusSnapshot;
+use crate::models::status_order::SortKey;
+use crate::services::{event_bus::EventBus, history::StatusHistory};
+use crate::utils::duration::format_duration;
 
-use super::controllers::current_status::CurrentStatusController;
+use super::controllers::current_status::{
+    Ack, CurrentStatusController, StatusEntry, DEFAULT_SNOOZE,
+};
+use super::theme::Theme;
+
+const THEME_KEY: &str = "status";
+
+/// Cards narrower than this get unreadable, the table takes over
+const CARD_MIN_WIDTH: u16 = 24;
+/// Title, description and job progress
+const CARD_HEIGHT: u16 = 3;
+const MAX_CARD_COLUMNS: usize = 4;
+/// Past this many entries the table is easier to scan than cards
+const MAX_CARDS: usize = 8;
+
+struct Styles {
+    heading: Style,
+    text: Style,
+    alert: Style,
+    warning: Style,
+    muted: Style,
+}
 
+impl Styles {
+    fn severity(&self, severity: &Severity) -> Style {
+        match severity {
+            Severity::Critical => self.alert,
+            Severity::Warning => self.warning,
+            Severity::Info => self.heading,
+        }
+    }
+}
+
+/// Active statuses as cards while there are a few of them, and as a table grouped
+/// by source once they no longer fit
 pub struct CurrentStatusWidget {
     controller: CurrentStatusController,
+    theme: Arc<Theme>,
+    /// Title of the entry actions apply to
+    selected: Option<String>,
+    /// Digits typed before `s`, snooze for that many minutes
+    count: String,
+    /// Picked with `o`, otherwise the configured order applies
+    sort: Option<SortKey>,
+    // first table row shown, kept on the selection while it moves
+    scroll: Cell<usize>,
+    follow_selection: bool,
 }
 
 impl CurrentStatusWidget {
-    pub async fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+    pub async fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        theme: Arc<Theme>,
+        history: Arc<Mutex<StatusHistory>>,
+        config: &StatusConfig,
+        reporters: &[String],
+    ) -> Self {
         Self {
-            controller: CurrentStatusController::new(event_bus),
+            controller: CurrentStatusController::new(event_bus, history, config, reporters),
+            theme,
+            selected: None,
+            count: String::new(),
+            sort: None,
+            scroll: Cell::new(0),
+            follow_selection: true,
+        }
+    }
+
+    fn primary_sort(&self) -> Option<&SortKey> {
+        self.sort.as_ref().or(self.controller.primary_sort())
+    }
+
+    /// Entries in the order they're shown
+    fn ordered(&self) -> (Vec<StatusEntry>, usize) {
+        self.controller.get_entries(self.primary_sort())
+    }
+
+    pub fn last_seen(&self, title: &str) -> Option<i64> {
+        self.controller.last_seen(title)
+    }
+
+    /// Entries in the order they're shown, and how many are snoozed
+    pub fn snapshot(&self) -> (Vec<StatusSnapshot>, usize) {
+        let (entries, snoozed) = self.ordered();
+        let statuses = entries
+            .into_iter()
+            .map(|entry| StatusSnapshot {
+                title: entry.msg.title().to_string(),
+                description: entry.msg.get_field_string(EventFieldType::Description),
+                severity: entry.severity.to_string().to_string(),
+                state: match entry.ack {
+                    Some(Ack::Acknowledged) => "acknowledged",
+                    _ => "active",
+                }
+                .to_string(),
+                group: entry.group,
+                since: entry.since,
+            })
+            .collect();
+        (statuses, snoozed)
+    }
+
+    /// Moves the selection, changes the sort order, scrolls the table and
+    /// acknowledges, dismisses or snoozes the selected entry
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let titles = self
+            .ordered()
+            .0
+            .into_iter()
+            .map(|entry| entry.msg.title().to_string())
+            .filter(|title| CurrentStatusController::is_actionable(title))
+            .collect::<Vec<String>>();
+        let position = self
+            .selected
+            .as_ref()
+            .and_then(|selected| titles.iter().position(|title| title == selected));
+
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                self.count.push(c);
+                return;
+            }
+            KeyCode::Right | KeyCode::Down | KeyCode::Char('l') | KeyCode::Char('j') => {
+                let next = position.map_or(0, |i| (i + 1) % titles.len().max(1));
+                self.selected = titles.get(next).cloned();
+                self.follow_selection = true;
+            }
+            KeyCode::Left | KeyCode::Up | KeyCode::Char('k') => {
+                let previous = match position {
+                    Some(0) | None => titles.len().saturating_sub(1),
+                    Some(i) => i - 1,
+                };
+                self.selected = titles.get(previous).cloned();
+                self.follow_selection = true;
+            }
+            KeyCode::PageDown => {
+                self.scroll.set(self.scroll.get() + 10);
+                self.follow_selection = false;
+            }
+            KeyCode::PageUp => {
+                self.scroll.set(self.scroll.get().saturating_sub(10));
+                self.follow_selection = false;
+            }
+            KeyCode::Char('o') => {
+                self.sort = Some(self.primary_sort().map_or(SortKey::Severity, SortKey::next))
+            }
+            KeyCode::Char('a') => {
+                if let Some(title) = &self.selected {
+                    self.controller.acknowledge(title);
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(title) = self.selected.take() {
+                    self.controller.dismiss(&title);
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(title) = self.selected.take() {
+                    let secs = self
+                        .count
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|minutes| *minutes > 0)
+                        .and_then(|minutes| minutes.checked_mul(60))
+                        .unwrap_or(DEFAULT_SNOOZE);
+                    self.controller.snooze(&title, secs);
+                }
+            }
+            KeyCode::Esc => self.selected = None,
+            _ => (),
+        }
+        self.count.clear();
+    }
+
+    fn maintenance_banner(&self, window: &MaintenanceWindow) -> Paragraph<'static> {
+        let until = Local
+            .timestamp_opt(window.until, 0)
+            .unwrap()
+            .format("%H:%M");
+        let text = if window.reason.is_empty() {
+            format!("MAINTENANCE until {until}")
+        } else {
+            format!("MAINTENANCE until {until} - {}", window.reason)
+        };
+
+        Paragraph::new(text)
+            .style(self.theme.style(THEME_KEY, "warning"))
+            .alignment(Alignment::Center)
+    }
+
+    /// `3m 2s elapsed, ~1m 5s left`, or a warning once the job is overdue
+    fn job_line(&self, job: &JobProgress) -> Paragraph<'static> {
+        let elapsed = format_duration(job.elapsed.max(0) as u64);
+        let (text, slot) = match (job.expected, job.eta()) {
+            (Some(expected), _) if job.overdue => (
+                format!(
+                    "{elapsed} elapsed, overdue (usually {})",
+                    format_duration(expected as u64)
+                ),
+                "warning",
+            ),
+            (_, Some(eta)) => (
+                format!("{elapsed} elapsed, ~{} left", format_duration(eta as u64)),
+                "muted",
+            ),
+            (Some(_), None) => (format!("{elapsed} elapsed, finishing"), "muted"),
+            (None, _) => (format!("{elapsed} elapsed"), "muted"),
+        };
+
+        Paragraph::new(text)
+            .style(self.theme.style(THEME_KEY, slot))
+            .alignment(Alignment::Center)
+    }
+
+    /// Heading and text style of an entry, acknowledged ones stop drawing attention
+    fn entry_styles(&self, entry: &StatusEntry, styles: &Styles) -> (Style, Style) {
+        let (mut heading, text) = if entry.ack == Some(Ack::Acknowledged) {
+            (styles.muted, styles.muted)
+        } else {
+            (styles.severity(&entry.severity), styles.text)
+        };
+        if self.selected.as_deref() == Some(entry.msg.title()) {
+            heading = heading.add_modifier(Modifier::REVERSED);
+        }
+        (heading, text)
+    }
+
+    fn entry_title(entry: &StatusEntry) -> String {
+        if entry.ack == Some(Ack::Acknowledged) {
+            format!("{} (ack)", entry.msg.title())
+        } else {
+            entry.msg.title().to_string()
+        }
+    }
+
+    /// How many card columns fit, None if the entries should go in a table
+    fn card_columns(count: usize, area: Rect) -> Option<usize> {
+        let columns = count
+            .min(MAX_CARD_COLUMNS)
+            .min((area.width / CARD_MIN_WIDTH).max(1) as usize);
+        let rows = count.div_ceil(columns);
+        // rows are separated by an empty line
+        let height = rows as u16 * (CARD_HEIGHT + 1) - 1;
+        (count <= MAX_CARDS && height <= area.height).then_some(columns)
+    }
+
+    fn render_cards(
+        &self,
+        entries: &[StatusEntry],
+        columns: usize,
+        styles: &Styles,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let rows = entries.chunks(columns).collect::<Vec<&[StatusEntry]>>();
+        let row_areas = Layout::vertical(vec![Constraint::Length(CARD_HEIGHT); rows.len()])
+            .flex(Flex::SpaceAround)
+            .split(area);
+
+        for (row, row_area) in rows.iter().zip(row_areas.iter()) {
+            // a partial last row stays centered under the full ones
+            let card_areas =
+                Layout::horizontal(vec![Constraint::Ratio(1, columns as u32); row.len()])
+                    .flex(Flex::Center)
+                    .split(*row_area);
+
+            for (entry, card_area) in row.iter().zip(card_areas.iter()) {
+                let (heading_style, text_style) = self.entry_styles(entry, styles);
+                let mut lines = vec![
+                    Paragraph::new(CurrentStatusWidget::entry_title(entry))
+                        .style(heading_style)
+                        .alignment(Alignment::Center),
+                    Paragraph::new(entry.msg.get_field_string(EventFieldType::Description))
+                        .style(text_style)
+                        .alignment(Alignment::Center),
+                ];
+                if let Some(job) = self.controller.get_job(entry.msg.title()) {
+                    lines.push(self.job_line(&job));
+                }
+
+                let line_areas = Layout::vertical(vec![Constraint::Max(1); lines.len()])
+                    .flex(Flex::Center)
+                    .split(*card_area);
+                for (line, line_area) in lines.iter().zip(line_areas.iter()) {
+                    line.render_ref(*line_area, buf);
+                }
+            }
+        }
+    }
+
+    fn render_table(&self, entries: &[StatusEntry], styles: &Styles, area: Rect, buf: &mut Buffer) {
+        let now = chrono::Utc::now().timestamp();
+        let mut rows = vec![];
+        let mut selected_row = None;
+
+        for (i, entry) in entries.iter().enumerate() {
+            if !entry.group.is_empty() && (i == 0 || entries[i - 1].group != entry.group) {
+                let count = entries.iter().filter(|e| e.group == entry.group).count();
+                rows.push(
+                    Row::new([format!("{} ({count})", entry.group)])
+                        .style(styles.muted.add_modifier(Modifier::BOLD)),
+                );
+            }
+            if self.selected.as_deref() == Some(entry.msg.title()) {
+                selected_row = Some(rows.len());
+            }
+
+            let (heading_style, text_style) = self.entry_styles(entry, styles);
+            rows.push(
+                Row::new([
+                    Line::from(entry.severity.to_string().to_string()).style(heading_style),
+                    Line::from(CurrentStatusWidget::entry_title(entry)).style(heading_style),
+                    Line::from(entry.msg.get_field_string(EventFieldType::Description))
+                        .style(text_style),
+                    Line::from(format_duration((now - entry.since).max(0) as u64))
+                        .style(text_style),
+                ])
+                .style(text_style),
+            );
+        }
+
+        // one row goes to the header
+        let height = area.height.saturating_sub(1) as usize;
+        let max_scroll = rows.len().saturating_sub(height);
+        let mut scroll = self.scroll.get();
+        if let Some(row) = selected_row.filter(|_| self.follow_selection) {
+            if row < scroll {
+                scroll = row;
+            } else if row >= scroll + height {
+                scroll = row + 1 - height;
+            }
         }
+        self.scroll.set(scroll.min(max_scroll));
+
+        let header = |label: &str, sort: SortKey| {
+            if self.primary_sort() == Some(&sort) {
+                format!("{label} ▼")
+            } else {
+                label.to_string()
+            }
+        };
+        let table = Table::new(
+            rows.into_iter().skip(self.scroll.get()),
+            [
+                // wide enough for the group rows
+                Constraint::Length(16),
+                Constraint::Percentage(30),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+            ],
+        )
+        .header(
+            Row::new([
+                header("Severity", SortKey::Severity),
+                header("Title", SortKey::Title),
+                "Description".to_string(),
+                header("Age", SortKey::Since),
+            ])
+            .style(self.theme.style(THEME_KEY, "heading")),
+        );
+        Widget::render(table, area, buf);
     }
 }
 
 impl WidgetRef for CurrentStatusWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered().title_bottom(Line::from(" Status ").red().bold());
-        let active_messages = self.controller.get_message_lock();
-
-        let layout =
-            utils::layout::make_layout(Direction::Horizontal, active_messages.len() as u16)
-                .flex(Flex::Center)
-                .split(block.inner(area));
-        let paragraphs = active_messages
-            .iter()
-            .map(|(_, v)| {
-                (
-                    Paragraph::new(v.title())
-                        .bold()
-                        .alignment(Alignment::Center),
-                    Paragraph::new(v.get_field_string(EventFieldType::Description))
-                        .alignment(Alignment::Center),
-                )
-            })
-            .collect::<Vec<(Paragraph, Paragraph)>>();
+        let (entries, snoozed) = self.ordered();
+        let mut block = Block::bordered()
+            .title_bottom(Line::from(" Status ").style(self.theme.style(THEME_KEY, "title")));
+        if snoozed > 0 {
+            block = block.title_bottom(
+                Line::from(format!(" {snoozed} snoozed "))
+                    .style(self.theme.style(THEME_KEY, "muted")),
+            );
+        }
+        if self.selected.is_some() {
+            let help = match self.count.as_str() {
+                "" => " a ack  d dismiss  s snooze ".to_string(),
+                count => format!(" s snooze {count}m "),
+            };
+            block = block.title_bottom(
+                Line::from(help)
+                    .style(self.theme.style(THEME_KEY, "muted"))
+                    .right_aligned(),
+            );
+        }
 
-        (0..paragraphs.len()).for_each(|i| {
-            let sub_layout = utils::layout::make_layout(Direction::Vertical, 2)
-                .constraints(vec![Constraint::Max(1); 2])
-                .flex(Flex::Center)
-                .split(layout[i]);
+        let mut inner = block.inner(area);
+        let mut styles = Styles {
+            heading: self.theme.style(THEME_KEY, "heading"),
+            text: self.theme.style(THEME_KEY, "text"),
+            alert: self.theme.style(THEME_KEY, "error"),
+            warning: self.theme.style(THEME_KEY, "warning"),
+            muted: self.theme.style(THEME_KEY, "muted"),
+        };
 
-            if let Some(elems) = paragraphs.get(i) {
-                let elems = elems.clone();
-                elems.0.render(sub_layout[0], buf);
-                elems.1.render(sub_layout[1], buf);
+        if let Some(window) = self.controller.get_maintenance() {
+            if window.mode == MaintenanceMode::Suppress {
+                let [banner, hidden] = Layout::vertical([Constraint::Max(1); 2])
+                    .flex(Flex::Center)
+                    .areas(inner);
+                self.maintenance_banner(&window).render(banner, buf);
+                Paragraph::new(format!("{} status entries hidden", entries.len()))
+                    .style(styles.muted)
+                    .alignment(Alignment::Center)
+                    .render(hidden, buf);
+                block.render(area, buf);
+                return;
             }
-        });
+
+            let [banner, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+            self.maintenance_banner(&window).render(banner, buf);
+            inner = rest;
+            styles.heading = styles.muted;
+            styles.text = styles.muted;
+            styles.alert = styles.muted;
+            styles.warning = styles.muted;
+        }
+
+        // with everything snoozed there's nothing left to show
+        if !entries.is_empty() {
+            match CurrentStatusWidget::card_columns(entries.len(), inner) {
+                Some(columns) => self.render_cards(&entries, columns, &styles, inner, buf),
+                None => {
+                    let block_title = format!(" o sort, {} entries ", entries.len());
+                    block = block.title_bottom(Line::from(block_title).style(styles.muted));
+                    self.render_table(&entries, &styles, inner, buf);
+                }
+            }
+        }
 
         block.render(area, buf);
     }
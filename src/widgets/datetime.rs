@@ -1,50 +1,124 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
 
+use chrono::{Datelike, Months, NaiveDate};
 use log::trace;
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Direction, Flex, Rect},
-    style::Stylize,
-    text::Line,
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, Paragraph, Widget, WidgetRef},
 };
 
-use crate::{services::event_bus::EventBus, utils};
+use crate::{models::config::ClockConfig, services::event_bus::EventBus};
+
+use super::{controllers::datetime::DateTimeController, theme::Theme};
 
-use super::controllers::datetime::DateTimeController;
+const THEME_KEY: &str = "time";
 
 pub struct DateTimeWidget {
     controller: DateTimeController,
+    theme: Arc<Theme>,
 }
 
 impl DateTimeWidget {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
-        Self {
-            controller: DateTimeController::new(event_bus),
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        theme: Arc<Theme>,
+        config: &ClockConfig,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            controller: DateTimeController::new(event_bus, config)?,
+            theme,
+        })
+    }
+
+    /// Month view with weeks starting on monday, today is highlighted
+    fn calendar_lines(today: NaiveDate, text: Style, today_style: Style) -> Vec<Line<'static>> {
+        let first = today.with_day(1).unwrap();
+        let days_in_month = (first + Months::new(1) - first).num_days() as u32;
+        let offset = first.weekday().num_days_from_monday() as usize;
+
+        let mut lines = vec![
+            Line::from(first.format("%B %Y").to_string()).style(text),
+            Line::from("Mo Tu We Th Fr Sa Su").style(text),
+        ];
+
+        // pad both ends, so every week lines up when centered
+        let mut cells: Vec<Span> = vec![Span::from("  "); offset];
+        for day in 1..=days_in_month {
+            let style = if day == today.day() {
+                today_style
+            } else {
+                text
+            };
+            cells.push(Span::styled(format!("{day:>2}"), style));
+        }
+        cells.resize(cells.len().div_ceil(7) * 7, Span::from("  "));
+
+        for week in cells.chunks(7) {
+            let mut spans = vec![];
+            for (i, cell) in week.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::from(" "));
+                }
+                spans.push(cell.clone());
+            }
+            lines.push(Line::from(spans));
         }
+
+        lines
     }
 }
 
 impl WidgetRef for DateTimeWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered().title_bottom(Line::from(" Time ").white().bold());
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Time ").style(self.theme.style(THEME_KEY, "title")));
 
-        let layout = utils::layout::make_layout(Direction::Horizontal, 1_u16)
-            .flex(Flex::Center)
-            .split(block.inner(area));
+        let heading = self.theme.style(THEME_KEY, "heading");
+        let text = self.theme.style(THEME_KEY, "text");
+        let muted = self.theme.style(THEME_KEY, "muted");
 
         let formatted_time = self.controller.get_formatted_time();
         trace!("formatted_time: {}", formatted_time);
-        let time = Paragraph::new(format!("{}\n\n{}", formatted_time, formatted_time))
-            .bold()
-            .alignment(Alignment::Center);
 
-        let sub_layout = utils::layout::make_layout(Direction::Vertical, 1_u16)
-            .constraints(vec![Constraint::Max(1); 1])
+        let mut lines = vec![
+            Line::from(formatted_time).style(heading),
+            Line::from(self.controller.get_formatted_date()).style(text),
+            Line::from(format!("up {}", self.controller.get_uptime())).style(muted),
+        ];
+
+        let zones = self.controller.get_zone_times();
+        if !zones.is_empty() {
+            lines.push(Line::default());
+        }
+        for (city, time) in zones {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{city} "), muted),
+                Span::styled(time, text),
+            ]));
+        }
+
+        if self.controller.calendar {
+            lines.push(Line::default());
+            lines.extend(DateTimeWidget::calendar_lines(
+                self.controller.get_today(),
+                text,
+                heading.reversed(),
+            ));
+        }
+
+        let [content] = Layout::vertical([Constraint::Length(lines.len() as u16)])
             .flex(Flex::Center)
-            .split(layout[0]);
+            .areas(block.inner(area));
 
-        time.render(sub_layout[0], buf);
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .render(content, buf);
         block.render(area, buf);
     }
 }
@@ -1,85 +1,117 @@
 use std::sync::{Arc, Mutex};
 
-use log::trace;
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Style, Stylize},
-    symbols,
-    widgets::{Axis, Chart, Dataset, GraphType, Widget, WidgetRef},
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Block, Paragraph, Widget, WidgetRef},
 };
 
-use crate::services::event_bus::EventBus;
+use crate::{
+    models::{config::ChartsConfig, metric_style::MetricStyle, time_series::TimeSeries},
+    services::event_bus::EventBus,
+};
+
+use super::{
+    controllers::hardware::HardwareUsageController,
+    metric::{legend_line, MetricVisual},
+    theme::Theme,
+};
 
-use super::controllers::hardware::HardwareUsageController;
+const THEME_KEY: &str = "hardware";
 
 pub struct HardwareUsageWidget {
     controller: HardwareUsageController,
+    theme: Arc<Theme>,
+    usage: MetricStyle,
+    load: MetricStyle,
+    network: MetricStyle,
 }
 
 impl HardwareUsageWidget {
-    pub fn new(event_bus: Arc<Mutex<EventBus>>) -> Self {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, theme: Arc<Theme>, config: &ChartsConfig) -> Self {
         Self {
             controller: HardwareUsageController::new(event_bus),
+            theme,
+            usage: config.usage.clone(),
+            load: config.load.clone(),
+            network: config.network.clone(),
         }
     }
-}
-
-impl WidgetRef for HardwareUsageWidget {
-    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let mut cpu_data: Vec<(f64, f64)> = vec![];
-        let mut ram_data: Vec<(f64, f64)> = vec![];
-        trace!("history: {}", self.controller.history as usize);
-        for i in 0..=self.controller.history as usize {
-            // break early if data hasn't been polled for that long
-            if i + 1 > self.controller.cpu_lock().len() {
-                break;
-            }
-            trace!("printing");
 
-            cpu_data.push((i as f64, self.controller.cpu_lock()[i]));
-            ram_data.push((i as f64, self.controller.ram_lock()[i]));
-        }
-        trace!("len cpu: {}", cpu_data.len());
-        trace!("len ram: {}", ram_data.len());
+    pub fn series(&self) -> Vec<TimeSeries> {
+        let series = self.controller.get_series();
+        vec![series.cpu, series.memory, series.load, series.rx, series.tx]
+    }
 
-        let datasets = vec![
-            Dataset::default()
-                .name("CPU")
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().blue().bold())
-                .data(&cpu_data),
-            Dataset::default()
-                .name("Memory")
-                .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
-                .style(Style::default().red().bold())
-                .data(&ram_data),
-        ];
+    /// One panel with its series drawn in `style` and a legend below
+    fn render_panel(
+        &self,
+        title: &str,
+        style: &MetricStyle,
+        series: &[(&TimeSeries, Style)],
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let block = Block::bordered().title_bottom(
+            Line::from(format!(" {title} ")).style(self.theme.style(THEME_KEY, "title")),
+        );
+        let inner = block.inner(area);
+        block.render(area, buf);
 
-        let mut labels: Vec<String> = vec![];
-        for i in 1..=2 {
-            labels.push((self.controller.history / i as f64).to_string());
-        }
-        labels.push("0".to_owned());
-        labels.reverse();
+        let [visual_area, legend_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(series.len() as u16)])
+                .areas(inner);
 
-        // Create the X axis and define its properties
-        let x_axis = Axis::default()
-            .style(Style::default().white())
-            .bounds([0.0, self.controller.history])
-            .labels(labels);
+        MetricVisual::new(style, series, &self.theme, THEME_KEY).render(visual_area, buf);
 
-        // Create the Y axis and define its properties
-        let y_axis = Axis::default()
-            .style(Style::default().white())
-            .bounds([0.0, 100.0])
-            .labels(["0", "25", "50", "75", "100"]);
+        Paragraph::new(
+            series
+                .iter()
+                .map(|(series, style)| legend_line(series, *style, &self.theme, THEME_KEY))
+                .collect::<Vec<Line>>(),
+        )
+        .render(legend_area, buf);
+    }
+}
 
-        // Create the chart and link all the parts together
-        let chart = Chart::new(datasets).x_axis(x_axis).y_axis(y_axis);
+impl WidgetRef for HardwareUsageWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let series = self.controller.get_series();
+        let [usage_area, load_area, network_area] = Layout::horizontal([
+            Constraint::Percentage(50),
+            Constraint::Percentage(20),
+            Constraint::Percentage(30),
+        ])
+        .areas(area);
 
-        chart.render(area, buf);
+        self.render_panel(
+            "Usage",
+            &self.usage,
+            &[
+                (&series.cpu, self.theme.style(THEME_KEY, "cpu")),
+                (&series.memory, self.theme.style(THEME_KEY, "memory")),
+            ],
+            usage_area,
+            buf,
+        );
+        self.render_panel(
+            "Load",
+            &self.load,
+            &[(&series.load, self.theme.style(THEME_KEY, "load"))],
+            load_area,
+            buf,
+        );
+        self.render_panel(
+            "Network",
+            &self.network,
+            &[
+                (&series.rx, self.theme.style(THEME_KEY, "rx")),
+                (&series.tx, self.theme.style(THEME_KEY, "tx")),
+            ],
+            network_area,
+            buf,
+        );
     }
 }
@@ -0,0 +1,113 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::{Arc, Mutex},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, WidgetRef},
+};
+use regex::Regex;
+
+use crate::{models::config::LogConfig, services::event_bus::EventBus};
+
+use super::{
+    controllers::logs::LogsController,
+    theme::{parse_style, Theme},
+};
+
+const THEME_KEY: &str = "logs";
+
+/// The newest lines of the tailed files, with the configured highlights
+pub struct LogsWidget {
+    controller: LogsController,
+    theme: Arc<Theme>,
+    highlights: Vec<(Regex, Style)>,
+}
+
+impl LogsWidget {
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        theme: Arc<Theme>,
+        config: &LogConfig,
+    ) -> io::Result<Self> {
+        let highlights = config
+            .highlight
+            .iter()
+            .map(|highlight| {
+                let regex = Regex::new(&highlight.pattern).map_err(|err| {
+                    io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid log highlight '{}': {err}", highlight.pattern),
+                    )
+                })?;
+                Ok((regex, parse_style(&highlight.style)?))
+            })
+            .collect::<io::Result<Vec<(Regex, Style)>>>()?;
+
+        Ok(Self {
+            controller: LogsController::new(event_bus, config.lines),
+            theme,
+            highlights,
+        })
+    }
+
+    /// Earlier highlights win where matches overlap
+    fn highlight(&self, line: &str, text: Style) -> Vec<Span<'static>> {
+        let mut ranges: Vec<(usize, usize, Style)> = vec![];
+        for (regex, style) in &self.highlights {
+            for found in regex.find_iter(line) {
+                let overlaps = ranges
+                    .iter()
+                    .any(|(start, end, _)| found.start() < *end && *start < found.end());
+                if !overlaps && !found.is_empty() {
+                    ranges.push((found.start(), found.end(), *style));
+                }
+            }
+        }
+        ranges.sort_by_key(|(start, _, _)| *start);
+
+        let mut spans = vec![];
+        let mut pos = 0;
+        for (start, end, style) in ranges {
+            if start > pos {
+                spans.push(Span::styled(line[pos..start].to_string(), text));
+            }
+            spans.push(Span::styled(
+                line[start..end].to_string(),
+                text.patch(style),
+            ));
+            pos = end;
+        }
+        if pos < line.len() {
+            spans.push(Span::styled(line[pos..].to_string(), text));
+        }
+        spans
+    }
+}
+
+impl WidgetRef for LogsWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Logs ").style(self.theme.style(THEME_KEY, "title")));
+        let height = block.inner(area).height as usize;
+        let text = self.theme.style(THEME_KEY, "text");
+        let muted = self.theme.style(THEME_KEY, "muted");
+
+        let lines = self.controller.lines_lock();
+        let lines = lines
+            .iter()
+            .skip(lines.len().saturating_sub(height))
+            .map(|(file, line)| {
+                let mut spans = vec![Span::styled(format!("{file} "), muted)];
+                spans.extend(self.highlight(line, text));
+                Line::from(spans)
+            })
+            .collect::<Vec<Line>>();
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
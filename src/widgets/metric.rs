@@ -0,0 +1,294 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Bar, BarChart, BarGroup, Chart, Dataset, Gauge, GraphType, Sparkline, Widget},
+};
+
+use crate::{
+    models::{
+        metric_style::MetricStyle,
+        time_series::{TimeSeries, Unit},
+    },
+    utils::duration::format_duration,
+};
+
+use super::theme::Theme;
+
+/// Shades of the heatmap, from nothing to the top of the scale
+const HEAT: [&str; 5] = [" ", "░", "▒", "▓", "█"];
+
+/// Sparklines and bars are scaled to this, they only take whole numbers
+const RESOLUTION: f64 = 1000.0;
+
+/// Any number of series drawn in one of the `MetricStyle`s. Line charts plot
+/// against time, the other styles use one column per sample with the newest on
+/// the right. All series share one scale, so they should have the same unit
+pub struct MetricVisual<'a> {
+    style: &'a MetricStyle,
+    series: &'a [(&'a TimeSeries, Style)],
+    theme: &'a Theme,
+    theme_key: &'a str,
+}
+
+impl<'a> MetricVisual<'a> {
+    pub fn new(
+        style: &'a MetricStyle,
+        series: &'a [(&'a TimeSeries, Style)],
+        theme: &'a Theme,
+        theme_key: &'a str,
+    ) -> Self {
+        Self {
+            style,
+            series,
+            theme,
+            theme_key,
+        }
+    }
+
+    fn unit(&self) -> Unit {
+        self.series
+            .first()
+            .map(|(series, _)| series.unit.clone())
+            .unwrap_or(Unit::Plain)
+    }
+
+    /// Top of the scale, percentages always go to 100 and everything else gets
+    /// some headroom above the highest value in the window
+    fn top(&self) -> f64 {
+        if self
+            .series
+            .iter()
+            .all(|(series, _)| series.unit == Unit::Percent)
+        {
+            return 100.0;
+        }
+
+        let max = self
+            .series
+            .iter()
+            .filter_map(|(series, _)| series.stats())
+            .map(|stats| stats.max * 1.1)
+            .chain(self.series.iter().map(|(series, _)| series.unit.min_axis()))
+            .fold(0.0, f64::max);
+
+        // round up to 1, 2 or 5 times a power of ten, so the labels stay readable
+        let magnitude = 10f64.powf(max.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|step| step * magnitude)
+            .find(|top| *top >= max)
+            .unwrap_or(10.0 * magnitude)
+    }
+
+    /// A share of the height for every series
+    fn rows(&self, area: Rect) -> Vec<Rect> {
+        Layout::vertical(vec![Constraint::Fill(1); self.series.len()])
+            .split(area)
+            .to_vec()
+    }
+
+    /// `values` are right aligned, so a series that hasn't filled the area yet
+    /// still ends at the right edge
+    fn align_right(area: Rect, width: usize) -> Rect {
+        let width = (width as u16).min(area.width);
+        Rect {
+            x: area.x + area.width - width,
+            width,
+            ..area
+        }
+    }
+
+    fn render_line(&self, area: Rect, buf: &mut Buffer) {
+        let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+        let window = self
+            .series
+            .iter()
+            .map(|(series, _)| series.window())
+            .fold(0.0, f64::max);
+        let points = self
+            .series
+            .iter()
+            .map(|(series, _)| series.relative_to(now))
+            .collect::<Vec<Vec<(f64, f64)>>>();
+
+        let datasets = self
+            .series
+            .iter()
+            .zip(points.iter())
+            .map(|((series, style), points)| {
+                Dataset::default()
+                    .name(series.name.clone())
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(*style)
+                    .data(points)
+            })
+            .collect::<Vec<Dataset>>();
+
+        let unit = self.unit();
+        let top = self.top();
+        let axis_style = self.theme.style(self.theme_key, "axis");
+        let mut x_axis = Axis::default().style(axis_style).bounds([-window, 0.0]);
+        let mut y_axis = Axis::default().style(axis_style).bounds([0.0, top]);
+        // small charts, like a single table row, only have room for the lines
+        if area.height >= 5 && area.width >= 20 {
+            let mut x_labels = vec![format!("-{}", format_duration(window as u64))];
+            // narrow charts only get the ends, the labels would run into each other
+            if area.width >= 40 {
+                x_labels.push(format!("-{}", format_duration(window as u64 / 2)));
+            }
+            x_labels.push("now".to_string());
+            x_axis = x_axis.labels(x_labels);
+            y_axis = y_axis.labels([unit.format(0.0), unit.format(top / 2.0), unit.format(top)]);
+        }
+
+        // panels show `legend_line`s, which have the current values
+        Chart::new(datasets)
+            .x_axis(x_axis)
+            .y_axis(y_axis)
+            .legend_position(None)
+            .render(area, buf);
+    }
+
+    fn render_sparkline(&self, area: Rect, buf: &mut Buffer) {
+        let top = self.top();
+        for ((series, style), row) in self.series.iter().zip(self.rows(area)) {
+            let values = series
+                .last(row.width as usize)
+                .into_iter()
+                .map(|value| (value / top * RESOLUTION).round() as u64)
+                .collect::<Vec<u64>>();
+            Sparkline::default()
+                .data(&values)
+                .max(RESOLUTION as u64)
+                .style(*style)
+                .render(MetricVisual::align_right(row, values.len()), buf);
+        }
+    }
+
+    fn render_gauge(&self, area: Rect, buf: &mut Buffer) {
+        let top = self.top();
+        for ((series, style), row) in self.series.iter().zip(self.rows(area)) {
+            let (ratio, label) = match series.stats() {
+                Some(stats) => (
+                    stats.current / top,
+                    format!("{} {}", series.name, series.unit.format(stats.current)),
+                ),
+                None => (0.0, series.name.clone()),
+            };
+            Gauge::default()
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(Span::styled(
+                    label,
+                    self.theme.style(self.theme_key, "text"),
+                ))
+                .gauge_style(*style)
+                .render(row, buf);
+        }
+    }
+
+    fn render_bar(&self, area: Rect, buf: &mut Buffer) {
+        if self.series.is_empty() {
+            return;
+        }
+        let top = self.top();
+        // a bar per series and a gap between samples
+        let group_width = self.series.len() + 1;
+        let count = (area.width as usize + 1) / group_width;
+        let values = self
+            .series
+            .iter()
+            .map(|(series, _)| series.last(count))
+            .collect::<Vec<Vec<f64>>>();
+        // series only differ in length until the window has filled up
+        let count = values.iter().map(Vec::len).min().unwrap_or_default();
+
+        let mut chart = BarChart::default()
+            .bar_width(1)
+            .bar_gap(0)
+            .group_gap(1)
+            .max(RESOLUTION as u64);
+        for sample in 0..count {
+            let bars = self
+                .series
+                .iter()
+                .zip(values.iter())
+                .map(|((_, style), values)| {
+                    let value = values[values.len() - count + sample];
+                    Bar::default()
+                        .value((value / top * RESOLUTION).round() as u64)
+                        .text_value(String::new())
+                        .style(*style)
+                })
+                .collect::<Vec<Bar>>();
+            chart = chart.data(BarGroup::default().bars(&bars));
+        }
+        chart.render(
+            MetricVisual::align_right(area, (count * group_width).saturating_sub(1)),
+            buf,
+        );
+    }
+
+    fn render_heatmap(&self, area: Rect, buf: &mut Buffer) {
+        let top = self.top();
+        for ((series, style), row) in self.series.iter().zip(self.rows(area)) {
+            let shades = series
+                .last(row.width as usize)
+                .into_iter()
+                .map(|value| {
+                    // anything above zero gets at least the lightest shade
+                    let shade = if value <= 0.0 {
+                        0
+                    } else {
+                        ((value / top * 4.0).ceil() as usize).clamp(1, 4)
+                    };
+                    HEAT[shade]
+                })
+                .collect::<String>();
+            let width = shades.chars().count();
+            let row = MetricVisual::align_right(row, width);
+            for y in row.top()..row.bottom() {
+                buf.set_stringn(row.x, y, &shades, row.width as usize, *style);
+            }
+        }
+    }
+}
+
+impl Widget for MetricVisual<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        match self.style {
+            MetricStyle::Line => self.render_line(area, buf),
+            MetricStyle::Sparkline => self.render_sparkline(area, buf),
+            MetricStyle::Gauge => self.render_gauge(area, buf),
+            MetricStyle::Bar => self.render_bar(area, buf),
+            MetricStyle::Heatmap => self.render_heatmap(area, buf),
+        }
+    }
+}
+
+/// `CPU 12%  min 2%  max 40%  avg 10%`
+pub fn legend_line(
+    series: &TimeSeries,
+    style: Style,
+    theme: &Theme,
+    theme_key: &str,
+) -> Line<'static> {
+    let Some(stats) = series.stats() else {
+        return Line::from(Span::styled(series.name.clone(), style));
+    };
+
+    let text = theme.style(theme_key, "text");
+    let muted = theme.style(theme_key, "muted");
+    let mut spans = vec![
+        Span::styled(format!("{} ", series.name), style),
+        Span::styled(series.unit.format(stats.current), text),
+    ];
+    for (label, value) in [("min", stats.min), ("max", stats.max), ("avg", stats.avg)] {
+        spans.push(Span::styled(format!("  {label} "), muted));
+        spans.push(Span::styled(series.unit.format(value), text));
+    }
+    Line::from(spans)
+}
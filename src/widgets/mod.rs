@@ -1,8 +1,17 @@
+pub mod certs;
+pub mod checks;
 pub mod controllers;
 pub mod current_status;
 pub mod datetime;
 pub mod disks;
 pub mod hardware;
 pub mod journalctl;
+pub mod logs;
+pub mod metric;
+pub mod plugins;
 pub mod podman;
+pub mod scripts;
+pub mod services;
 pub mod systemctl_stats;
+pub mod theme;
+pub mod timeline;
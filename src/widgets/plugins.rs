@@ -0,0 +1,83 @@
+use std::sync::{Arc, Mutex};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Widget, WidgetRef},
+};
+
+use crate::{
+    models::{config::ChartsConfig, metric_style::MetricStyle, time_series::TimeSeries},
+    services::event_bus::EventBus,
+};
+
+use super::{controllers::plugins::PluginsController, metric::MetricVisual, theme::Theme};
+
+const THEME_KEY: &str = "plugins";
+
+/// One line per metric reported by a plugin or script, with its values over time
+pub struct PluginsWidget {
+    controller: PluginsController,
+    theme: Arc<Theme>,
+    style: MetricStyle,
+}
+
+impl PluginsWidget {
+    pub fn new(
+        event_bus: Arc<Mutex<EventBus>>,
+        theme: Arc<Theme>,
+        reporters: &[String],
+        config: &ChartsConfig,
+    ) -> Self {
+        Self {
+            controller: PluginsController::new(event_bus, reporters),
+            theme,
+            style: config.plugins.clone(),
+        }
+    }
+
+    /// Metrics seen so far, the panel only takes up space once there are any
+    pub fn count(&self) -> usize {
+        self.controller.metrics_lock().len()
+    }
+
+    pub fn series(&self) -> Vec<TimeSeries> {
+        self.controller.metrics_lock().clone()
+    }
+}
+
+impl WidgetRef for PluginsWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Plugins ").style(self.theme.style(THEME_KEY, "title")));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let metrics = self.controller.metrics_lock();
+        let rows = Layout::vertical(vec![Constraint::Length(1); metrics.len()]).split(inner);
+        let style = self.theme.style(THEME_KEY, "ok");
+
+        for (series, row) in metrics.iter().zip(rows.iter()) {
+            let value = series
+                .stats()
+                .map(|stats| series.unit.format(stats.current))
+                .unwrap_or_default();
+
+            let [text_area, visual_area] =
+                Layout::horizontal([Constraint::Percentage(70), Constraint::Fill(1)]).areas(*row);
+
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", series.name),
+                    self.theme.style(THEME_KEY, "heading"),
+                ),
+                Span::styled(value, self.theme.style(THEME_KEY, "text")),
+            ])
+            .render(text_area, buf);
+
+            MetricVisual::new(&self.style, &[(series, style)], &self.theme, THEME_KEY)
+                .render(visual_area, buf);
+        }
+    }
+}
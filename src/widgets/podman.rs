@@ -1,32 +1,52 @@
+use std::sync::Arc;
+
 use ratatui::text::Line;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
-    style::{Style, Stylize},
     widgets::{Block, Paragraph, Widget, WidgetRef},
 };
 
-pub struct PodmanWidget {}
+use crate::models::snapshot::ContainerSnapshot;
+
+use super::theme::Theme;
+
+const THEME_KEY: &str = "podman";
+
+pub struct PodmanWidget {
+    theme: Arc<Theme>,
+}
 
 impl PodmanWidget {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(theme: Arc<Theme>) -> Self {
+        Self { theme }
+    }
+
+    /// Container counts, not polled from podman yet
+    pub fn snapshot(&self) -> ContainerSnapshot {
+        ContainerSnapshot {
+            all: 0,
+            running: 0,
+            failed: 0,
+        }
     }
 }
 
 impl WidgetRef for PodmanWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered().title_bottom(Line::from(" Podman ").magenta().bold());
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Podman ").style(self.theme.style(THEME_KEY, "title")));
 
+        let containers = self.snapshot();
+        let text = self.theme.style(THEME_KEY, "text");
         let mut paragraphs: Vec<Paragraph> = vec![
-            Paragraph::new(format!("All containers: {}", 0)),
-            Paragraph::new(format!("Running containers: {}", 0)),
+            Paragraph::new(format!("All containers: {}", containers.all)).style(text),
+            Paragraph::new(format!("Running containers: {}", containers.running)).style(text),
         ];
 
         paragraphs.push(
-            Paragraph::new(format!("FAILED CONTAINERS: {}", 0))
-                .style(Style::default().bold().red().slow_blink())
-                .slow_blink(),
+            Paragraph::new(format!("FAILED CONTAINERS: {}", containers.failed))
+                .style(self.theme.style(THEME_KEY, "error")),
         );
 
         let areas = Layout::vertical(
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Gauge, Widget, WidgetRef},
+};
+
+use crate::{
+    models::event_bus_field_type::EventFieldType, services::event_bus::EventBus,
+    utils::bytes_helper::bytes_to_f64,
+};
+
+use super::{controllers::scripts::ScriptsController, theme::Theme};
+
+const THEME_KEY: &str = "scripts";
+
+/// One line per script that called `show` or `gauge`
+pub struct ScriptsWidget {
+    controller: ScriptsController,
+    theme: Arc<Theme>,
+}
+
+impl ScriptsWidget {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, theme: Arc<Theme>) -> Self {
+        Self {
+            controller: ScriptsController::new(event_bus),
+            theme,
+        }
+    }
+
+    /// Scripts that showed something, the panel only takes up space once there are any
+    pub fn count(&self) -> usize {
+        self.controller.displays_lock().len()
+    }
+}
+
+impl WidgetRef for ScriptsWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title_bottom(Line::from(" Scripts ").style(self.theme.style(THEME_KEY, "title")));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let displays = self.controller.displays_lock();
+        let rows = Layout::vertical(vec![Constraint::Length(1); displays.len()]).split(inner);
+
+        for (display, row) in displays.iter().zip(rows.iter()) {
+            let [name_area, content_area] = Layout::horizontal([
+                Constraint::Length(display.title().chars().count() as u16 + 1),
+                Constraint::Fill(1),
+            ])
+            .areas(*row);
+            Span::styled(display.title(), self.theme.style(THEME_KEY, "heading"))
+                .render(name_area, buf);
+
+            let text = display.get_field_string(EventFieldType::Description);
+            if display.has_field(EventFieldType::Value) {
+                Gauge::default()
+                    .ratio(bytes_to_f64(display.get_field(EventFieldType::Value)))
+                    .label(Span::styled(text, self.theme.style(THEME_KEY, "text")))
+                    .gauge_style(self.theme.style(THEME_KEY, "ok"))
+                    .render(content_area, buf);
+            } else {
+                Span::styled(text, self.theme.style(THEME_KEY, "text")).render(content_area, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, WidgetRef},
+};
+
+use crate::{
+    models::{
+        event_bus_field_type::EventFieldType,
+        service_state::ServiceState,
+        snapshot::{MetricSnapshot, ServiceSnapshot},
+    },
+    services::event_bus::EventBus,
+};
+
+use super::{controllers::services::ServicesController, theme::Theme};
+
+const THEME_KEY: &str = "services";
+
+pub struct ServicesWidget {
+    controller: ServicesController,
+    theme: Arc<Theme>,
+}
+
+impl ServicesWidget {
+    pub fn new(event_bus: Arc<Mutex<EventBus>>, theme: Arc<Theme>) -> Self {
+        Self {
+            controller: ServicesController::new(event_bus),
+            theme,
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<ServiceSnapshot> {
+        self.controller
+            .services_lock()
+            .iter()
+            .map(|msg| ServiceSnapshot {
+                name: msg.title().to_string(),
+                state: msg.get_field_string(EventFieldType::State),
+                error: msg
+                    .has_field(EventFieldType::Error)
+                    .then(|| msg.get_field_string(EventFieldType::Error)),
+            })
+            .collect()
+    }
+
+    /// The dashboard's own usage, as shown in the corner of the panel
+    pub fn self_usage_snapshot(&self) -> Vec<MetricSnapshot> {
+        let Some(usage) = self.controller.get_self_usage() else {
+            return vec![];
+        };
+        [
+            ("self cpu", "percent", usage.cpu),
+            ("self memory", "bytes", usage.memory),
+        ]
+        .into_iter()
+        .map(|(name, unit, value)| MetricSnapshot {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            current: value,
+            min: value,
+            max: value,
+            avg: value,
+        })
+        .collect()
+    }
+}
+
+impl WidgetRef for ServicesWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let mut block = Block::bordered()
+            .title_bottom(Line::from(" Services ").style(self.theme.style(THEME_KEY, "title")));
+        // keeping an eye on the dashboard's own overhead
+        if let Some(usage) = self.controller.get_self_usage() {
+            block = block.title_bottom(
+                Line::from(format!(
+                    " {:.1}% {:.0} MB ",
+                    usage.cpu,
+                    usage.memory / 1_000_000.0
+                ))
+                .style(self.theme.style(THEME_KEY, "muted"))
+                .right_aligned(),
+            );
+        }
+
+        let mut lines: Vec<Line> = vec![];
+        for msg in self.controller.services_lock().iter() {
+            let state = ServiceState::from_string(&msg.get_field_string(EventFieldType::State));
+            let state_style = match state {
+                ServiceState::Running => self.theme.style(THEME_KEY, "ok"),
+                ServiceState::Restarting => self.theme.style(THEME_KEY, "error"),
+                ServiceState::Stopped => self.theme.style(THEME_KEY, "muted"),
+            };
+
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{} ", msg.title()),
+                    self.theme.style(THEME_KEY, "heading"),
+                ),
+                Span::styled(state.to_string().to_owned(), state_style),
+            ]));
+
+            if msg.has_field(EventFieldType::Error) {
+                lines.push(
+                    Line::from(format!("  {}", msg.get_field_string(EventFieldType::Error)))
+                        .style(self.theme.style(THEME_KEY, "muted")),
+                );
+            }
+        }
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
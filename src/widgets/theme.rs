@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, ErrorKind},
+    str::FromStr,
+};
+
+use ratatui::style::{Color, Modifier, Style, Stylize};
+
+use crate::models::config::ThemeConfig;
+
+pub const THEME_ENV: &str = "SERVER_TUI_THEME";
+pub const DEFAULT_THEME: &str = "default";
+
+/// Styles are looked up by widget and slot. Widgets use the slots `title`,
+/// `heading`, `text`, `ok`, `warning`, `error`, `muted`, `axis`, `cpu`, `memory`,
+/// `load`, `rx` and `tx`,
+/// and a palette can set a slot for every widget (`error`) or a single one (`status.title`)
+pub struct Theme {
+    styles: HashMap<String, Style>,
+}
+
+impl Theme {
+    /// Picks the palette from `$SERVER_TUI_THEME` or the config, then applies the
+    /// per-widget overrides. `NO_COLOR` and dumb terminals always get the monochrome
+    /// palette, but explicit overrides from the config still apply on top of it
+    pub fn from_config(config: &ThemeConfig) -> io::Result<Self> {
+        let name = env::var(THEME_ENV)
+            .ok()
+            .or(config.name.clone())
+            .unwrap_or(DEFAULT_THEME.to_string());
+
+        let palette = if Theme::colors_disabled() {
+            Theme::monochrome()
+        } else {
+            match name.as_str() {
+                "default" => Theme::default_palette(),
+                "solarized" => Theme::solarized(),
+                "high-contrast" => Theme::high_contrast(),
+                "monochrome" => Theme::monochrome(),
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("unknown theme '{name}'"),
+                    ))
+                }
+            }
+        };
+
+        let mut styles: HashMap<String, Style> = palette
+            .into_iter()
+            .map(|(key, style)| (key.to_string(), style))
+            .collect();
+
+        for (widget, slots) in &config.widgets {
+            for (slot, style) in slots {
+                styles.insert(format!("{widget}.{slot}"), parse_style(style)?);
+            }
+        }
+
+        Ok(Self { styles })
+    }
+
+    /// Looks up `widget.slot`, falling back to the palette wide `slot`
+    pub fn style(&self, widget: &str, slot: &str) -> Style {
+        self.styles
+            .get(&format!("{widget}.{slot}"))
+            .or(self.styles.get(slot))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn colors_disabled() -> bool {
+        env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty())
+            || env::var("TERM").is_ok_and(|term| term == "dumb")
+    }
+
+    fn default_palette() -> Vec<(&'static str, Style)> {
+        vec![
+            ("title", Style::default().white().bold()),
+            ("heading", Style::default().bold()),
+            ("text", Style::default()),
+            ("ok", Style::default().green()),
+            ("warning", Style::default().yellow().bold()),
+            ("error", Style::default().red().bold()),
+            ("muted", Style::default().dark_gray()),
+            ("axis", Style::default().white()),
+            ("cpu", Style::default().blue().bold()),
+            ("memory", Style::default().red().bold()),
+            ("load", Style::default().yellow()),
+            ("rx", Style::default().green()),
+            ("tx", Style::default().magenta()),
+            ("status.title", Style::default().red().bold()),
+            ("podman.title", Style::default().magenta().bold()),
+            ("podman.error", Style::default().red().bold().slow_blink()),
+            ("services.title", Style::default().cyan().bold()),
+        ]
+    }
+
+    fn solarized() -> Vec<(&'static str, Style)> {
+        const BASE01: Color = Color::Rgb(0x58, 0x6e, 0x75);
+        const BASE0: Color = Color::Rgb(0x83, 0x94, 0x96);
+        const BASE1: Color = Color::Rgb(0x93, 0xa1, 0xa1);
+        const YELLOW: Color = Color::Rgb(0xb5, 0x89, 0x00);
+        const RED: Color = Color::Rgb(0xdc, 0x32, 0x2f);
+        const MAGENTA: Color = Color::Rgb(0xd3, 0x36, 0x82);
+        const BLUE: Color = Color::Rgb(0x26, 0x8b, 0xd2);
+        const CYAN: Color = Color::Rgb(0x2a, 0xa1, 0x98);
+        const GREEN: Color = Color::Rgb(0x85, 0x99, 0x00);
+
+        vec![
+            ("title", Style::default().fg(BASE1).bold()),
+            ("heading", Style::default().fg(BASE1).bold()),
+            ("text", Style::default().fg(BASE0)),
+            ("ok", Style::default().fg(GREEN)),
+            ("warning", Style::default().fg(YELLOW).bold()),
+            ("error", Style::default().fg(RED).bold()),
+            ("muted", Style::default().fg(BASE01)),
+            ("axis", Style::default().fg(BASE0)),
+            ("cpu", Style::default().fg(BLUE).bold()),
+            ("memory", Style::default().fg(MAGENTA).bold()),
+            ("load", Style::default().fg(YELLOW)),
+            ("rx", Style::default().fg(GREEN)),
+            ("tx", Style::default().fg(CYAN)),
+            ("status.title", Style::default().fg(RED).bold()),
+            ("podman.title", Style::default().fg(MAGENTA).bold()),
+            ("services.title", Style::default().fg(CYAN).bold()),
+        ]
+    }
+
+    fn high_contrast() -> Vec<(&'static str, Style)> {
+        vec![
+            ("title", Style::default().white().bold()),
+            ("heading", Style::default().white().bold()),
+            ("text", Style::default().white()),
+            ("ok", Style::default().light_green().bold()),
+            ("warning", Style::default().black().on_light_yellow().bold()),
+            ("error", Style::default().white().on_red().bold()),
+            ("muted", Style::default().gray()),
+            ("axis", Style::default().white().bold()),
+            ("cpu", Style::default().light_cyan().bold()),
+            ("memory", Style::default().light_magenta().bold()),
+            ("load", Style::default().light_yellow().bold()),
+            ("rx", Style::default().light_green().bold()),
+            ("tx", Style::default().light_red().bold()),
+        ]
+    }
+
+    /// Only uses modifiers, for `NO_COLOR` and terminals without colour support
+    fn monochrome() -> Vec<(&'static str, Style)> {
+        vec![
+            ("title", Style::default().bold()),
+            ("heading", Style::default().bold()),
+            ("warning", Style::default().bold()),
+            ("error", Style::default().bold().reversed()),
+            ("muted", Style::default().dim()),
+            ("cpu", Style::default().bold()),
+            ("rx", Style::default().bold()),
+        ]
+    }
+}
+
+/// Parses styles like `red`, `#ff0000 bold` or `black on yellow underlined`
+pub fn parse_style(input: &str) -> io::Result<Style> {
+    let invalid = |token: &str| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid style '{input}', unknown '{token}'"),
+        )
+    };
+
+    let mut style = Style::default();
+    let mut tokens = input.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let modifier = match token.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "slow_blink" => Modifier::SLOW_BLINK,
+            "crossed_out" => Modifier::CROSSED_OUT,
+            "on" => {
+                let bg = tokens.next().ok_or(invalid(token))?;
+                style = style.bg(Color::from_str(bg).map_err(|_| invalid(bg))?);
+                continue;
+            }
+            _ => {
+                style = style.fg(Color::from_str(token).map_err(|_| invalid(token))?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+
+    Ok(style)
+}
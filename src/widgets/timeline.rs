@@ -0,0 +1,170 @@
+use std::{
+    cell::Cell,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{Local, TimeZone};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    text::Line,
+    widgets::{Block, Clear, Row, Table, Widget, WidgetRef},
+};
+
+use crate::{
+    models::{event_type::EventType, status_event::StatusTransition},
+    services::history::StatusHistory,
+    utils::{self, duration::format_duration},
+};
+
+use super::{controllers::timeline::TimelineController, theme::Theme};
+
+const THEME_KEY: &str = "timeline";
+const SOURCES: [Option<EventType>; 3] = [None, Some(EventType::Socket), Some(EventType::Process)];
+
+/// Popup listing every status transition, scrollable and filterable by title and source
+pub struct TimelineWidget {
+    controller: TimelineController,
+    theme: Arc<Theme>,
+    scroll: usize,
+    // updated on render, so scrolling stops at the last page
+    max_scroll: Cell<usize>,
+    title_filter: String,
+    editing_filter: bool,
+    source: usize,
+}
+
+impl TimelineWidget {
+    pub fn new(history: Arc<Mutex<StatusHistory>>, theme: Arc<Theme>) -> Self {
+        Self {
+            controller: TimelineController::new(history),
+            theme,
+            scroll: 0,
+            max_scroll: Cell::new(0),
+            title_filter: String::new(),
+            editing_filter: false,
+            source: 0,
+        }
+    }
+
+    /// Returns false once the popup should be closed
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                KeyCode::Backspace => {
+                    self.title_filter.pop();
+                }
+                KeyCode::Char(c) => self.title_filter.push(c),
+                _ => (),
+            }
+            self.scroll = 0;
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('h') | KeyCode::Char('q') => return false,
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Tab => {
+                self.source = (self.source + 1) % SOURCES.len();
+                self.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll += 10,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll = 0,
+            _ => (),
+        }
+        self.scroll = self.scroll.min(self.max_scroll.get());
+        true
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        SOURCES[self.source].as_ref().map(|source| source.name())
+    }
+}
+
+impl WidgetRef for TimelineWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let area = utils::layout::centered(area, 80, 80);
+        let text = self.theme.style(THEME_KEY, "text");
+        let muted = self.theme.style(THEME_KEY, "muted");
+
+        let filter = if self.editing_filter {
+            format!(" filter: {}_ ", self.title_filter)
+        } else if self.title_filter.is_empty() {
+            " / filter ".to_string()
+        } else {
+            format!(" filter: {} ", self.title_filter)
+        };
+
+        let block = Block::bordered()
+            .title(Line::from(" Timeline ").style(self.theme.style(THEME_KEY, "title")))
+            .title_bottom(Line::from(filter).style(muted))
+            .title_bottom(
+                Line::from(format!(
+                    " tab source: {} ",
+                    self.source_name().unwrap_or("all")
+                ))
+                .style(muted),
+            )
+            .title_bottom(Line::from(" esc close ").style(muted).right_aligned());
+
+        let events = self
+            .controller
+            .get_events(&self.title_filter, self.source_name());
+        // one row goes to the header
+        let height = block.inner(area).height.saturating_sub(1) as usize;
+        self.max_scroll.set(events.len().saturating_sub(height));
+        let scroll = self.scroll.min(self.max_scroll.get());
+
+        let rows = events.iter().skip(scroll).take(height).map(|event| {
+            let style = match event.transition {
+                StatusTransition::Started => self.theme.style(THEME_KEY, "ok"),
+                StatusTransition::Updated => text,
+                StatusTransition::Done => self.theme.style(THEME_KEY, "heading"),
+                StatusTransition::Failed => self.theme.style(THEME_KEY, "error"),
+                StatusTransition::Expired => self.theme.style(THEME_KEY, "warning"),
+                StatusTransition::Acknowledged
+                | StatusTransition::Dismissed
+                | StatusTransition::Snoozed => muted,
+            };
+            let ts = Local.timestamp_opt(event.ts, 0).unwrap();
+
+            Row::new(vec![
+                ts.format("%Y-%m-%d %H:%M:%S").to_string(),
+                event.source.clone(),
+                event.title.clone(),
+                event.transition.to_string().to_string(),
+                event
+                    .duration
+                    .map(|secs| format_duration(secs.max(0) as u64))
+                    .unwrap_or_default(),
+                event.description.clone(),
+            ])
+            .style(style)
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(19),
+                Constraint::Length(8),
+                Constraint::Percentage(25),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(["Time", "Source", "Title", "Event", "Took", "Description"])
+                .style(self.theme.style(THEME_KEY, "heading")),
+        )
+        .block(block);
+
+        Clear.render(area, buf);
+        Widget::render(table, area, buf);
+    }
+}